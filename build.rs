@@ -35,17 +35,97 @@ pub fn main() {
 fn build_cli() {
 	let mut builder = KeyWordsBuilder::default();
 	builder.push_keys([
+		"--allow-scripts",
+		"--check-well-formed",
+		"--clean-attr-whitespace",
+		"--doctype",
+		"--dry-run",
+		"--dump-ast",
+		"--emit-dimensions",
+		"--error-context",
+		"--fail-on-diff",
+		"--gzip",
 		"-h", "--help",
 		"--hidden",
+		"--html-fragment",
+		"--if-changed",
+		"--inherit-root-presentation",
+		"--integer-viewbox",
+		"--keep-data",
+		"--minify",
+		"--mono",
+		"--namespace-ids",
+		"--no-prefix",
+		"--no-text",
+		"--normalize-ids-to-prefix",
 		"--offscreen",
+		"--prefix-from-output",
+		"--read-id-comments",
+		"--reject-external",
+		"--relaxed-ids",
+		"--report-by-dir",
+		"--report-colors",
+		"--report-control-chars",
+		"--report-derived-viewbox",
+		"--report-empty",
+		"--report-fonts",
+		"--report-paths",
+		"--report-schema",
+		"--report-strokes",
+		"--report-unused-namespaces",
+		"--selftest",
+		"--split-multi",
+		"--strict",
+		"--strip-version",
+		"--themable",
+		"--timing",
+		"--trim-path-whitespace",
+		"-v", "--verbose",
 		"-V", "--version",
+		"--warn-fixed-dimensions",
+		"--warn-odd-sizes",
+		"--warn-title-mismatch",
+		"--warn-zero-shapes",
+		"--warnings-summary",
 	]);
 	builder.push_keys_with_values([
+		"--also-individual",
+		"--attribute-order",
+		"--compare",
+		"--css",
+		"--demo",
+		"--error-on",
+		"--exclude",
+		"--hide",
+		"--id-lock",
+		"--id-map",
+		"--id-template",
+		"--keep-empty",
+		"--keep-root-attributes",
 		"-l", "--list",
+		"--log-level",
 		"--map-class",
+		"--max-depth",
+		"--max-id-length",
 		"--map-id",
+		"--max-total-bytes",
+		"--max-warnings",
+		"--mode",
 		"-o", "--output",
+		"--output-mode",
+		"--output-style",
+		"--parse-timeout",
+		"--precision",
 		"-p", "--prefix",
+		"--report",
+		"--self-closing",
+		"--sort",
+		"--stdin",
+		"--symbol-class",
+		"--usage-data",
+		"--usage-href-style",
+		"--used-ids",
+		"--viewbox-source",
 	]);
 	builder.save(out_path("argyle.rs"));
 }
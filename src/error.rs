@@ -2,9 +2,11 @@
 # Yesvgmap: Errors
 */
 
+use crate::ContentWarnings;
 use std::{
 	error::Error,
 	fmt,
+	num::NonZeroUsize,
 	path::PathBuf,
 };
 
@@ -39,35 +41,347 @@ USAGE:
     yesvgmap [FLAGS] [OPTIONS] <PATH(S)>
 
 FLAGS:
+        --allow-scripts         Never treat scripts (<script> elements and on*
+                                event attributes) as errors, even if --error-on
+                                says otherwise. They are still reported as
+                                warnings.
+        --check-well-formed     Hold each source to the XML well-formedness
+                                rules the (lenient) parser otherwise lets slide,
+                                such as balanced tags, quoted attribute values,
+                                and escaped ampersands, failing on the first
+                                violation.
+        --clean-attr-whitespace
+                                Collapse runs of whitespace (e.g. newlines and
+                                tabs) within attribute values to single spaces,
+                                except inside quoted strings.
+        --doctype               Precede the map with the SVG 1.1 DOCTYPE
+                                declaration, for strict XHTML consumers. This is
+                                ignored for --html-fragment output.
+        --dry-run               Build the map, running all checks and reports,
+                                but skip writing any files or printing the map.
+        --dump-ast              Print the parser's view of each source image —
+                                normalized tags, attributes, text, and errors —
+                                to STDERR, then exit without building a map.
+        --emit-dimensions       Add data-width and data-height attributes to
+                                each symbol matching its viewBox dimensions.
+        --error-context         Include a snippet of the source near the point
+                                of failure in parse error messages.
+        --fail-on-diff          Exit with an error if --compare finds any
+                                differences.
+        --gzip                  Also save a gzipped copy of the map alongside
+                                the -o/--output file, with .gz appended to its
+                                name, or only the gzipped version if the output
+                                path already ends with .gz.
     -h, --help                  Print help information and exit.
         --hidden                Hide the map using the "hidden" HTML attribute.
                                 This takes priority over --offscreen when both
                                 are present.
+        --html-fragment         Wrap the map in a <div style="display:none"
+                                aria-hidden="true"> container, ready for direct
+                                inclusion in an HTML template.
+        --if-changed            Leave the -o/--output map (and any gzipped copy)
+                                untouched if it already matches the new one
+                                byte-for-byte, so its modification time is
+                                preserved and file watchers aren't retriggered.
+        --inherit-root-presentation
+                                Wrap each symbol's content in a group carrying
+                                any presentation attributes — fill, stroke,
+                                opacity, etc. — set on the source root <svg>, so
+                                their inheritance is preserved.
+        --integer-viewbox       Round fractional viewBox widths and heights to
+                                whole numbers (e.g. "0 0 23.5 24" becomes "0 0
+                                24 24") for crisper pixel-snapped rendering,
+                                with a warning for each adjustment.
+        --keep-data             Copy data-* attributes (e.g. data-category) from
+                                each source's root <svg> to its <symbol>. (Those
+                                on child elements are always kept.)
+        --minify                Rewrite path data (d and points values) as
+                                compactly as possible, dropping redundant
+                                whitespace, separators, and leading zeros,
+                                without changing any of the numbers.
+        --mono                  Replace concrete fill and stroke colors with
+                                currentColor so icons can be recolored via CSS.
+                                Values like none and url(…) gradient references
+                                are left alone, as is mask content.
+        --namespace-ids         Prefix each source's internal IDs with its
+                                symbol ID to avoid cross-file collisions. This
+                                is equivalent to --normalize-ids-to-prefix.
+        --no-prefix             Use the bare (sanitized) file stems as the
+                                symbol IDs, without any prefix. The stems must
+                                begin with a letter (see --relaxed-ids). This
+                                cannot be combined with -p/--prefix or --prefix-
+                                from-output.
+        --no-text               Treat text content (within <text>, <tspan>, or
+                                <textPath> elements) as an error rather than a
+                                warning, since its rendering depends on font
+                                availability. This is equivalent to --error-on
+                                text.
+        --normalize-ids-to-prefix
+                                Rewrite the IDs of elements within each symbol
+                                as SYMBOLID__ORIGINAL, updating any local
+                                references (e.g. url(#...) and href attributes),
+                                so they are unique and easy to trace.
         --offscreen             Hide the map using inline styles to position it
                                 offscreen.
+        --prefix-from-output    Derive the symbol ID prefix from the --output
+                                file name (minus any leading "sprite-"), e.g.
+                                "sprite-nav.svg" becomes "nav". An explicit
+                                --prefix takes priority.
+        --read-id-comments      Use the ID declared by a <!-- yesvgmap-id: NAME
+                                --> comment ahead of a source's root <svg> tag
+                                in place of its file stem. (IDs mapped via --id-
+                                map take priority.)
+        --reject-external       Treat external references, data URIs, and raster
+                                images as errors rather than warnings. This is
+                                equivalent to --error-on external-ref,data-
+                                uri,raster.
+        --relaxed-ids           Allow generated IDs (i.e. from --id-template or
+                                --prefix-from-output) to begin with a digit or
+                                underscore, e.g. "2048-game". Browsers handle
+                                these fine, but they are not valid XML names and
+                                need escaping in CSS selectors.
+        --report-by-dir         List the number of symbols sourced from each
+                                directory, most first.
+        --report-colors         List the distinct colors (e.g. fill, stroke, and
+                                stop-color values, whether attributes or inline
+                                styles) used across all symbols, most common
+                                first.
+        --report-control-chars  Warn about any stray (non-whitespace) control
+                                characters, such as null bytes, in attribute
+                                values or text.
+        --report-derived-viewbox
+                                List any symbols whose viewBox was derived from
+                                the source width and height (because it had no
+                                viewBox of its own).
+        --report-empty          List any symbols with nothing to render (i.e. no
+                                content besides a title, description, or
+                                metadata), such as sources whose elements were
+                                all stripped as empty.
+        --report-fonts          List the distinct font-family values (whether
+                                attributes or inline styles) used across all
+                                symbols, most common first, i.e. the fonts the
+                                map assumes will be available.
+        --report-paths          List each included source file along with its
+                                resulting symbol ID.
+        --report-schema         Print the JSON Schema for the --report output
+                                and exit.
+        --report-strokes        List the symbols containing stroked elements
+                                without a vector-effect, as such strokes will
+                                scale along with the icon.
+        --report-unused-namespaces
+                                List any symbols declaring xmlns:* namespace
+                                prefixes (e.g. editor leftovers like xmlns:cc)
+                                that nothing within them uses, so they can be
+                                removed.
+        --selftest              Run the built-in test fixtures through the
+                                parser, print the results, and exit.
+        --split-multi           Split sources containing multiple top-level
+                                <symbol> or <g id> icons into separate symbols,
+                                with IDs built from the inner IDs. Top-level
+                                <defs> and <style> are copied into each.
+        --strict                Treat all content warnings as errors, along with
+                                any control characters, zero-dimension shapes,
+                                or fractional viewBoxes found by --report-
+                                control-chars, --warn-zero-shapes, or --integer-
+                                viewbox.
+        --strip-version         Remove version and baseProfile attributes from
+                                the symbols and their children (including any
+                                carried over by --keep-root-attributes).
+        --themable              Replace the colors of multi-color symbols with
+                                CSS custom properties — var(--icon-color-N,
+                                ORIGINAL) — so they can be themed, noting the
+                                variables in a comment.
+        --timing                Print a summary of how long crawling, parsing,
+                                and serialization took, along with the slowest
+                                source, to STDERR at the end.
+        --trim-path-whitespace  Collapse whitespace runs in d and points values
+                                to single spaces, and drop any around commas,
+                                e.g. 'M 0 , 0  L 10  10' becomes 'M 0,0 L 10
+                                10'. Unlike --minify, commands and numbers are
+                                left exactly as they were.
+    -v, --verbose               With --dry-run, print a per-file preview of the
+                                symbol IDs, viewBoxes, sources, and notable
+                                adjustments.
     -V, --version               Print version information and exit.
+        --warn-fixed-dimensions Warn about any sources whose root has absolute
+                                width/height units (e.g. 24px) alongside a
+                                viewBox, since they will render at that fixed
+                                size when used directly.
+        --warn-odd-sizes        Warn about any symbols whose viewBox dimensions
+                                differ from the most common size in the set.
+        --warn-title-mismatch   Warn about any symbols whose <title> doesn't
+                                seem to match the source file name, e.g. a
+                                close.svg titled "Menu".
+        --warn-zero-shapes      Warn about any shapes with zero-valued
+                                dimensions (e.g. <rect width="0"> or <circle
+                                r="0">), since they will not render.
+        --warnings-summary      Summarize any content warnings by category —
+                                with a count and a truncated list of the
+                                affected files — instead of listing them file by
+                                file.
 
 OPTIONS:
+        --also-individual <DIR> Alongside the map, save each symbol as a
+                                standalone SVG image in this directory, named
+                                after its ID (e.g. i-close.svg).
+        --attribute-order <LIST>
+                                Serialize these (comma-separated, case-
+                                sensitive) attributes first, in this order,
+                                ahead of the rest (which remain alphabetical),
+                                e.g. xmlns,id,viewBox.
+        --compare <FILE>        Compare the generated map against this
+                                previously generated one, listing the symbols
+                                added, removed, or changed.
+        --css <PATH>            Save a stylesheet to this path with an .icon-ID
+                                rule for each symbol, setting the standalone
+                                image as a data-URI background-image.
+        --demo <FILE>           Save a standalone HTML page to this location
+                                with the map inlined and a labeled preview of
+                                each symbol, handy for confirming everything
+                                renders correctly.
+        --error-on <LIST>       Treat these (comma-separated) kinds of
+                                potentially problematic content as errors
+                                rather than warnings. Categories: script,
+                                style, inline-style, class, id, external-ref,
+                                data-uri, raster, duplicate-attr, text.
+        --exclude <PATTERN>     Skip any crawled paths matching this pattern — a
+                                glob if it contains *, ?, or [, otherwise a
+                                substring — checked against the full path. This
+                                option may be repeated.
+        --hide <MODE>           Hide the map with an inline display:none style
+                                ("display"), the "hidden" HTML attribute
+                                ("hidden"), inline styles positioning it
+                                offscreen ("offscreen"), or not at all ("none").
+                                Note that some older browsers won't paint
+                                symbols within a display:none map. [default:
+                                none]
+        --id-lock <FILE>        Record the emitted symbol IDs in this lockfile,
+                                and refuse to generate the sprite if any are
+                                already claimed by a different output file.
+                                Share one lock across runs to keep IDs unique
+                                across multiple sprites. Requires -o.
+        --id-map <FILE>         Read source-stem<TAB>id pairs from this file,
+                                one per line, using the mapped id in place of
+                                the sanitized file stem for any matching
+                                sources, e.g. Icon_24px_Final_v3<TAB>close for
+                                i-close. Other sources are named as usual.
+        --id-template <TPL>     Build symbol IDs from this template instead of
+                                the usual PREFIX-STEM. Supported placeholders
+                                are {prefix}, {stem}, {dir} (the parent
+                                directory name), and {hash} (a short content
+                                hash, lengthened as needed to keep IDs unique).
+                                The result is reduced to ASCII alphanumerics,
+                                dashes, and underscores, and must begin with a
+                                letter.
+        --keep-empty <LIST>     Never drop these (comma-separated) tags, even if
+                                they have no attributes or children (e.g.
+                                marker,mask).
+        --keep-root-attributes <LIST>
+                                Copy these (comma-separated) attributes from
+                                each source root <svg> element to its <symbol>,
+                                e.g. fill,stroke,overflow. The id, viewBox,
+                                width, height, x, and y attributes cannot be
+                                used.
     -l, --list <FILE>           Read (absolute) file and/or directory paths
                                 from this text file — or STDIN if "-" — one
                                 entry per line, instead of or addition to
                                 (actually trailing) <PATH(S)>.
+        --log-level <LEVEL>     Print log messages at or above this level to
+                                STDERR: error, warn, info, or debug, the last of
+                                which traces each file and transformation. This
+                                can also be set with the YESVGMAP_LOG
+                                environment variable. [default: info]
         --map-class <CLASS>     Add this class to the generated SVG map.
                                 [default: ]
         --map-id <ID>           Add this ID to the generated SVG map.
                                 [default: ]
-    -o, --output <PATH>         Save the generated map to this location. If
-                                omitted, the map will print to STDOUT instead.
+        --max-depth <NUM>       Abort if any source has elements nested more
+                                than this many levels deep. [default: 256]
+        --max-id-length <NUM>   Abort if any generated symbol ID is longer than
+                                this many characters.
+        --max-total-bytes <NUM> Abort if the generated map would exceed this
+                                many bytes.
+        --max-warnings <NUM>    Fail if the total number of content warnings —
+                                counting each category separately, per file —
+                                exceeds this limit. Use 0 to allow none.
+        --mode <MODE>           Wrap each image in a <symbol> ("symbol"), or in
+                                a <g> within a single shared <defs> ("defs").
+                                Groups have no viewBox of their own, so any
+                                offset is applied via a translate transform
+                                instead. [default: symbol]
+    -o, --output <PATH>         Save the generated map to this location, which
+                                must end with .svg (or .svg.gz, or .html for
+                                --html-fragment); .svg is appended if there is
+                                no extension. If omitted, the map will print to
+                                STDOUT instead.
+        --output-mode <MODE>    Write the -o/--output map (and any gzipped copy)
+                                via a temporary file renamed into place
+                                ("atomic"), or by overwriting the existing file
+                                in place, keeping its inode, for FIFOs or
+                                watched files ("truncate"). [default: atomic]
+        --output-style <STYLE>  Serialize the map on one line ("compact"), one
+                                element per line, indented with tabs ("pretty"),
+                                or on one line with the whitespace runs within
+                                attribute values collapsed to a single space,
+                                leaving text, scripts, styles, and
+                                xml:space=preserve content alone ("minified").
+                                [default: compact]
+        --parse-timeout <MS>    Abort if parsing any single source takes longer
+                                than this many milliseconds, so one pathological
+                                file cannot hang the whole run.
+        --precision <NUM>       Round viewBox widths and heights to this many
+                                decimal places (0 for whole numbers). Values
+                                that would round to zero are left as-is.
     -p, --prefix <STRING>       Set a custom prefix for the IDs of each entry
                                 in the map. (IDs look like PREFIX-STEM, where
                                 STEM is the alphanumeric portion of the source
                                 file name, e.g. "i-close".) [default: i]
+        --report <FILE>         Save a JSON report detailing the symbols, per-
+                                file warnings, and aggregate stats to this
+                                location.
+        --self-closing <MODE>   Serialize childless elements in "always" self-
+                                closing form (e.g. <path/>) or "never" (e.g.
+                                <path></path>). [default: always]
+        --sort <MODE>           Sort the symbols by "alpha" (ID), "alpha-desc"
+                                (ID, reversed), "file" (the order the sources
+                                were given or found), "mtime" (source
+                                modification time, oldest first), or "usage"
+                                (most used first, per --usage-data). If the
+                                modification times are all identical,
+                                alphabetical order is used instead.
+                                [default: alpha]
+        --stdin <ID>            Read an SVG image from STDIN, adding it to the
+                                map under this (verbatim) ID. A lone - argument
+                                does the same, using the ID PREFIX-stdin.
+        --symbol-class <CLASS>  Add this class to every symbol, alongside any
+                                source class kept via --keep-root-attributes.
+        --usage-data <FILE>     Read id<TAB>count pairs from this file, one per
+                                line, for --sort usage. Symbols are ordered by
+                                descending count, then ID; any not listed come
+                                last, alphabetically.
+        --usage-href-style <STYLE>
+                                Point the <use> references in the --demo page at
+                                their symbols via href ("href"), the legacy
+                                xlink:href for older browsers ("xlink"), or both
+                                ("both"). The sprite itself is unaffected.
+                                [default: href]
+        --used-ids <FILE>       Read the symbol IDs your project actually uses
+                                from this text file, one per line, and warn
+                                about any symbols in the map that are not among
+                                them.
+        --viewbox-source <MODE> Take each symbol viewBox from the source root
+                                viewBox, falling back to its width and height
+                                ("auto"), from the viewBox alone, erroring if
+                                missing ("viewbox"), or from the width and
+                                height alone, ignoring any viewBox ("width-
+                                height"). [default: auto]
 
 ARGS:
     <PATH(S)>...                One or more file and/or directory paths to
-                                crunch and/or (recursively) crawl. Only files
-                                with the extension .svg will ultimately be
-                                included.
+                                crunch and/or (recursively) crawl, or glob
+                                patterns like 'icons/**/*.svg' to expand. Only
+                                files with the extension .svg will ultimately
+                                be included.
 "#);
 
 
@@ -75,21 +389,78 @@ ARGS:
 #[derive(Debug, Clone)]
 /// # Error type.
 pub(super) enum SvgError {
+	/// # Conflicting CLI Options.
+	CliConflict(&'static str, &'static str),
+
+	/// # Missing CLI Option.
+	CliRequires(&'static str, &'static str),
+
+	/// # Invalid CLI Value.
+	CliValue(&'static str, String),
+
+	/// # Disallowed Content.
+	Disallowed(Vec<(PathBuf, ContentWarnings)>),
+
 	/// # Duplicate entry.
 	Duplicate(String),
 
 	/// # File Name (Stem).
 	FileName(PathBuf),
 
+	/// # Invalid Output Path.
+	InvalidDst(PathBuf),
+
+	/// # ID Already Claimed.
+	IdClaimed(PathBuf, Vec<(String, String)>),
+
+	/// # Invalid ID Comment.
+	IdComment(PathBuf, String),
+
+	/// # ID Too Long.
+	IdLength(String, NonZeroUsize),
+
+	/// # Invalid ID Start.
+	IdStart(PathBuf, String),
+
+	/// # Malformed XML.
+	Malformed(PathBuf, String),
+
+	/// # Nesting Too Deep.
+	NestingTooDeep(PathBuf, NonZeroUsize),
+
 	/// # No SVGs.
 	NoSvgs,
 
 	/// # Parse.
 	Parse(PathBuf),
 
+	/// # Parse (No Opening Tag).
+	ParseStart(PathBuf, String),
+
+	/// # Parse Timeout.
+	ParseTimeout(PathBuf),
+
+	/// # Parse (With Context).
+	ParseContext(PathBuf, String),
+
 	/// # SVG Read.
 	Read(PathBuf),
 
+	/// # Map Differences.
+	SpriteDiff(usize),
+
+	/// # Map Too Large.
+	SpriteTooLarge(usize, NonZeroUsize),
+
+	/// # Self-Test Failure(s).
+	SelfTest(usize),
+
+	/// # Strict Violations.
+	StrictWarnings(Vec<(String, String)>),
+
+	/// # Too Many Warnings.
+	TooManyWarnings(usize, usize),
+
 	/// # Viewbox.
 	Viewbox(PathBuf),
 
@@ -106,12 +477,61 @@ pub(super) enum SvgError {
 impl Error for SvgError {}
 
 impl fmt::Display for SvgError {
+	#[expect(clippy::unnecessary_debug_formatting, reason = "Some messages quote their paths.")]
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
+			Self::CliConflict(a, b) => write!(f, "{a} and {b} cannot be used together."),
+			Self::CliRequires(a, b) => write!(f, "{a} requires {b}."),
+			Self::CliValue(k, v) => write!(f, "Invalid {k} value: {v}"),
+			Self::Disallowed(list) => {
+				f.write_str("Disallowed content was found:")?;
+				for (p, w) in list {
+					write!(f, "\n    \x1b[1;91m•\x1b[0m {p:?} \x1b[2m({w})\x1b[0m")?;
+				}
+				Ok(())
+			},
 			Self::Duplicate(s) => write!(f, "Normalized name collision: {s}."),
 			Self::FileName(p) => write!(f, "File name has no ASCII alphanumeric or '-': {p:?}"),
+			Self::InvalidDst(p) => write!(
+				f,
+				"Output path must end with .svg or .svg.gz (or .html/.htm with --html-fragment): {p:?}",
+			),
+			Self::IdClaimed(p, list) => {
+				write!(f, "Symbol IDs already claimed in {p:?}:")?;
+				for (id, owner) in list {
+					write!(f, "\n    \x1b[1;91m•\x1b[0m {id} \x1b[2m({owner})\x1b[0m")?;
+				}
+				Ok(())
+			},
+			Self::IdComment(p, id) => write!(f, "Invalid yesvgmap-id comment: {id:?} ({p:?})"),
+			Self::IdLength(id, max) => write!(f, "Symbol ID exceeds {max} characters: {id}"),
+			Self::IdStart(p, id) => write!(f, "Symbol ID must begin with a letter: {id} ({p:?})"),
+			Self::Malformed(p, s) => write!(f, "Malformed XML: {p:?}; {s}."),
+			Self::NestingTooDeep(p, max) => write!(f, "Elements are nested more than {max} levels deep: {p:?}"),
 			Self::Parse(p) => write!(f, "Unable to parse: {p:?}."),
+			Self::ParseStart(p, s) => write!(f, "Missing <svg> tag: {p:?}; it begins: {s}"),
+			Self::ParseTimeout(p) => write!(f, "Parsing took too long: {p:?}."),
+			Self::ParseContext(p, s) => write!(f, "Unable to parse: {p:?}, near: {s}"),
 			Self::Read(p) => write!(f, "Unreadable: {p:?}."),
+			Self::SpriteDiff(n) => write!(
+				f,
+				"The map differs from the comparison by {n} symbol{}.",
+				if *n == 1 { "" } else { "s" },
+			),
+			Self::SpriteTooLarge(len, max) => write!(f, "The map is {len} bytes, exceeding the {max}-byte budget."),
+			Self::SelfTest(n) => write!(f, "Self-test failed for {n} fixture(s)."),
+			Self::StrictWarnings(list) => {
+				f.write_str("Strict mode violations were found:")?;
+				for (id, issue) in list {
+					write!(f, "\n    \x1b[1;91m•\x1b[0m {id} \x1b[2m({issue})\x1b[0m")?;
+				}
+				Ok(())
+			},
+			Self::TooManyWarnings(n, max) => write!(
+				f,
+				"Found {n} content warning{}, exceeding the maximum of {max}.",
+				if *n == 1 { "" } else { "s" },
+			),
 			Self::Viewbox(p) => write!(f, "Missing viewBox: {p:?}"),
 			_ => f.write_str(self.as_str()),
 		}
@@ -2,20 +2,39 @@
 # Yesvgmap: Boundaries
 */
 
-use crate::SvgError;
+use crate::{
+	ContentWarnings,
+	log,
+	MapOptions,
+	SvgError,
+};
 use fyi_msg::Msg;
+use rayon::iter::{
+	IntoParallelRefIterator,
+	ParallelIterator,
+};
 use std::{
 	borrow::Cow,
-	collections::BTreeMap,
+	collections::{
+		BTreeMap,
+		BTreeSet,
+	},
 	fmt,
+	num::NonZeroUsize,
 	path::{
 		Path,
 		PathBuf,
 	},
+	time::{
+		Duration,
+		Instant,
+		SystemTime,
+	},
 };
 use svg::{
 	node::{
 		Attributes,
+		Comment,
 		element::{
 			Element,
 			Symbol,
@@ -33,6 +52,40 @@ use svg::{
 
 
 
+/// # Minimum Hash Length.
+///
+/// This is the number of hex digits initially used for `{hash}` ID template
+/// placeholders.
+const HASH_LEN_MIN: usize = 8;
+
+/// # Maximum Hash Length.
+const HASH_LEN_MAX: usize = 16;
+
+/// # SVG 1.1 Doctype.
+const DOCTYPE: &str = r#"<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">"#;
+
+/// # Presentation Attributes.
+///
+/// These inheritable attributes, when set on a source's root `<svg>`, can be
+/// carried over to a wrapping group (see `--inherit-root-presentation`).
+const PRESENTATION_ATTRS: [&str; 24] = [
+	"clip-rule", "color", "color-interpolation", "color-rendering", "cursor",
+	"fill", "fill-opacity", "fill-rule", "font-family", "font-size",
+	"font-style", "font-weight", "opacity", "paint-order", "shape-rendering",
+	"stroke", "stroke-dasharray", "stroke-dashoffset", "stroke-linecap",
+	"stroke-linejoin", "stroke-miterlimit", "stroke-opacity", "stroke-width",
+	"visibility",
+];
+
+/// # Color Properties.
+///
+/// These attributes (or style properties) take color values.
+const COLOR_PROPS: [&str; 6] = [
+	"color", "fill", "flood-color", "lighting-color", "stop-color", "stroke",
+];
+
+
+
 #[derive(Debug, Clone)]
 /// # SVG Map.
 ///
@@ -44,55 +97,622 @@ pub(super) struct Map {
 	/// # Hide Type.
 	hide: HideType,
 
+	/// # Sprite Mode.
+	mode: SpriteMode,
+
+	/// # Leading Attributes.
+	///
+	/// These attributes, when present, are serialized first, in this order;
+	/// everything else follows alphabetically.
+	attribute_order: Vec<String>,
+
+	/// # Self-Closing Style.
+	self_closing: SelfClosing,
+
+	/// # Doctype?
+	///
+	/// If `true`, the map is preceded by the SVG 1.1 `DOCTYPE` (unless it is
+	/// an HTML fragment).
+	doctype: bool,
+
+	/// # HTML Fragment?
+	///
+	/// If `true`, the map is wrapped in a hidden `<div>` for direct inclusion
+	/// in HTML documents.
+	html_fragment: bool,
+
 	/// # Length.
 	len: usize,
+
+	/// # Symbol Details.
+	symbols: Vec<MapSymbol>,
+
+	/// # Warnings.
+	warnings: Vec<(PathBuf, ContentWarnings)>,
+
+	/// # Parse Times.
+	///
+	/// How long each source took to parse, in source order.
+	parse_times: Vec<(PathBuf, Duration)>,
+
+	/// # Input Bytes.
+	///
+	/// The combined size of the raw sources.
+	input_bytes: usize,
 }
 
 impl fmt::Display for Map {
 	/// # To String.
 	///
-	/// This emits SVG code, slightly compressed.
+	/// This emits SVG code, slightly compressed (i.e. [`OutputStyle::Compact`]).
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		// Stringify the SVG.
-		let mut raw = self.inner.to_string();
+		f.write_str(&self.render(OutputStyle::Compact))
+	}
+}
+
+impl Map {
+	/// # New (With In-Memory Sources).
+	///
+	/// Build a map from the files on disk as well as any in-memory
+	/// `(id, raw_svg)` pairs, e.g. an image read from STDIN. The latter's IDs
+	/// are used as-is — no prefix or template is applied — and double as the
+	/// "source" for warning and error purposes.
+	pub(super) fn with_sources(
+		opts: &MapOptions,
+		paths: &[PathBuf],
+		sources: &[(&str, &str)],
+	) -> Result<Self, SvgError> {
+		// There have to be paths.
+		if paths.is_empty() && sources.is_empty() {
+			return Err(SvgError::NoSvgs);
+		}
+
+		// Handle the paths! Reading and parsing are done in parallel, but the
+		// results are added in order so the IDs — and any errors — are the
+		// same from run to run.
+		let loaded: Vec<Result<LoadedSource, SvgError>> = paths.par_iter()
+			.map(|path| LoadedSource::new(opts, path))
+			.collect();
+
+		let mut builder = MapBuilder::new(opts);
+		for (path, loaded) in paths.iter().zip(loaded) {
+			let LoadedSource { stem, raw, id, parsed, mtime, elapsed } = loaded?;
 
-		// The hidden attribute shouldn't have a "true" attached to it.
-		if matches!(self.hide, HideType::Hidden) {
-			if let Some(pos) = raw.find(r#" hidden="true""#) {
-				raw.replace_range(pos+7..pos+14, "");
+			// Content hashes start out short, but are lengthened as needed
+			// to avoid collisions. On the rare occasion that changes the ID,
+			// the source has to be parsed again.
+			let (id, (s, warn, derived)) =
+				if let Some(tpl) = opts.id_template.as_deref() {
+					let unique = unique_id(
+						|len| parse_id_template(tpl, &opts.prefix, path, &stem, &raw, opts.relaxed_ids, len),
+						|id| builder.contains(id),
+					)
+						.ok_or_else(|| SvgError::FileName(path.clone()))?;
+					if unique == id { (id, parsed) }
+					else {
+						let parsed = parse_svg(&raw, path, &unique, opts)?;
+						(unique, parsed)
+					}
+				}
+				else { (id, parsed) };
+
+			// Split multi-icon sources into separate symbols?
+			let parts = if opts.split_multi { split_symbol(&s) } else { None };
+			if let Some(parts) = parts {
+				for (inner, mut s, inherited) in parts {
+					let id =
+						if let Some(tpl) = opts.id_template.as_deref() {
+							unique_id(
+								|len| parse_id_template(tpl, &opts.prefix, path, &inner, &raw, opts.relaxed_ids, len),
+								|id| builder.contains(id),
+							)
+								.ok_or_else(|| SvgError::FileName(path.clone()))?
+						}
+						else {
+							opts.symbol_id(&inner)
+								.ok_or_else(|| SvgError::IdStart(path.clone(), inner.clone()))?
+						};
+
+					log::debug(format_args!("{}: split out {inner} as ID {id}.", path.display()));
+					s.assign("id", id.as_str());
+					builder.push_symbol(id, path, &raw, s, warn, derived && inherited, mtime)?;
+				}
 			}
+			else {
+				log::debug(format_args!("{}: using ID {id}.", path.display()));
+				builder.push_symbol(id, path, &raw, s, warn, derived, mtime)?;
+			}
+
+			builder.parse_times.push((path.clone(), elapsed));
+			builder.input_bytes += raw.len();
+		}
+
+		// And the in-memory sources.
+		for (id, raw) in sources {
+			let now = Instant::now();
+			builder.push((*id).to_owned(), Path::new(id), raw, None)?;
+			builder.parse_times.push((PathBuf::from(id), now.elapsed()));
+			builder.input_bytes += raw.len();
 		}
 
-		// Clean up whitespace a bit.
-		let mut out = String::with_capacity(raw.len());
-		let mut last = '?';
-		let mut iter = raw.chars().peekable();
-		while let Some(c) = iter.next() {
-			if  c == '\n' && (last == '>' || iter.peek() == Some(&'<')) {
-				continue;
+		builder.finish()
+	}
+
+	/// # Render (With Budget).
+	///
+	/// Serialize the map as with [`Map::render`], but return an error if the
+	/// result exceeds `max` bytes.
+	pub(super) fn render_within(&self, style: OutputStyle, max: Option<NonZeroUsize>)
+	-> Result<String, SvgError> {
+		let out = self.render(style);
+		if let Some(max) = max {
+			if max.get() < out.len() {
+				return Err(SvgError::SpriteTooLarge(out.len(), max));
 			}
+		}
+		Ok(out)
+	}
 
-			last = c;
-			out.push(c);
+	/// # Render.
+	///
+	/// Serialize the map using the given style.
+	pub(super) fn render(&self, style: OutputStyle) -> String {
+		let out = self.render_svg(style);
+		let sep = if matches!(style, OutputStyle::Pretty) { "\n" } else { "" };
+		if self.html_fragment {
+			format!(r#"<div style="display:none" aria-hidden="true">{sep}{out}{sep}</div>"#)
+		}
+		else if self.doctype { format!("{DOCTYPE}{sep}{out}") }
+		else { out }
+	}
+
+	/// # Render (HTML).
+	///
+	/// Serialize the map compactly for inclusion in an HTML document, i.e.
+	/// wrapped in a hidden `<div>` — as with `--html-fragment` — and without
+	/// any `DOCTYPE`.
+	pub(super) fn render_html(&self) -> String {
+		format!(
+			r#"<div style="display:none" aria-hidden="true">{}</div>"#,
+			self.render_svg(OutputStyle::Compact),
+		)
+	}
+
+	/// # Render SVG.
+	///
+	/// Serialize the `<svg>` itself using the given style.
+	fn render_svg(&self, style: OutputStyle) -> String {
+		match style {
+			OutputStyle::Compact => self.render_compact(),
+			OutputStyle::Pretty => {
+				let mut out = String::with_capacity(self.len * 512);
+				render_pretty(
+					&*self.root(),
+					0,
+					matches!(self.hide, HideType::Hidden),
+					&self.attribute_order,
+					self.self_closing,
+					&mut out,
+				);
+				out.truncate(out.trim_end().len());
+				out
+			},
+			OutputStyle::Minified => {
+				let mut root = self.root().into_owned();
+				minify_attr_whitespace(&mut root);
+
+				let mut out = String::with_capacity(self.len * 512);
+				render_compact(
+					&root,
+					matches!(self.hide, HideType::Hidden),
+					&self.attribute_order,
+					self.self_closing,
+					&mut out,
+				);
+				out
+			},
+		}
+	}
+
+	/// # Render Compact.
+	///
+	/// This emits the elements back-to-back, without any formatting
+	/// whitespace between them.
+	fn render_compact(&self) -> String {
+		let mut out = String::with_capacity(self.len * 512);
+		render_compact(
+			&*self.root(),
+			matches!(self.hide, HideType::Hidden),
+			&self.attribute_order,
+			self.self_closing,
+			&mut out,
+		);
+		out
+	}
+
+	/// # Root.
+	///
+	/// Return the `<svg>` as it should be serialized. For
+	/// [`SpriteMode::Defs`], this is rebuilt on-the-fly; everything else
+	/// works off the `<symbol>`-based original.
+	fn root(&self) -> Cow<'_, SVG> {
+		match self.mode {
+			SpriteMode::Symbol => Cow::Borrowed(&self.inner),
+			SpriteMode::Defs => Cow::Owned(defs_root(&self.inner)),
 		}
+	}
+
+	/// # Parse Times.
+	///
+	/// Return how long each source took to parse, in source order.
+	pub(super) fn parse_times(&self) -> &[(PathBuf, Duration)] { &self.parse_times }
+
+	/// # Input Bytes.
+	///
+	/// Return the combined size of the raw sources, in bytes.
+	pub(super) const fn input_bytes(&self) -> usize { self.input_bytes }
+
+	/// # Individual Images.
+	///
+	/// Return each symbol as a standalone `<svg>` — with the same `viewBox`,
+	/// content, and other attributes, minus the `id` — paired with its ID.
+	pub(super) fn individual(&self) -> Vec<(String, String)> {
+		self.inner.get_children().map_or(&[][..], Vec::as_slice).iter()
+			.filter(|c| c.get_name() == "symbol")
+			.filter_map(|s| {
+				let attrs = s.get_attributes()?;
+				let id = attrs.get("id")?.to_string();
+
+				let mut svg = Element::new("svg");
+				svg.assign("xmlns", "http://www.w3.org/2000/svg");
+				for (k, v) in attrs {
+					if k != "id" { svg.assign(k.as_str(), v.clone()); }
+				}
+				Element::get_children_mut(&mut svg).extend(s.get_children()?.iter().cloned());
+
+				let mut out = String::new();
+				render_compact(&svg, false, &self.attribute_order, self.self_closing, &mut out);
+				Some((id, out))
+			})
+			.collect()
+	}
+
+	/// # Length.
+	///
+	/// Return the number of children (`symbol` elements).
+	pub(super) const fn len(&self) -> usize { self.len }
+
+	/// # Symbols.
+	///
+	/// Return the details for each symbol in the map, in order.
+	pub(super) fn symbols(&self) -> &[MapSymbol] { &self.symbols }
 
-		// Print it!
-		f.write_str(&out)
+	/// # Warnings.
+	///
+	/// Return the source paths and content warnings for any images that had
+	/// potentially problematic content, ordered by file name.
+	pub(super) fn warnings(&self) -> &[(PathBuf, ContentWarnings)] {
+		&self.warnings
+	}
+
+	/// # Print Warnings.
+	///
+	/// Print a list of the images with potentially problematic content, if
+	/// any, to STDERR.
+	fn print_warnings(&self) {
+		let warnings = self.warnings();
+		if warnings.is_empty() { return; }
+
+		Msg::warning(format!(
+			"Scripts, styles, classes, IDs, and external resources may not work
+correctly in sprite map contexts; the following image{} might need to be
+refactored:",
+			if warnings.len() == 1 { "" } else { "s" },
+		))
+			.eprint();
+
+		for (p, w) in warnings {
+			if let Some(name) = p.file_name() {
+				eprintln!(
+					"    \x1b[1;93m•\x1b[0m {} \x1b[2m({w})\x1b[0m",
+					Path::new(name).display(),
+				);
+			}
+		}
 	}
 }
 
-impl Map {
+
+
+/// # Loaded Source.
+///
+/// This holds a source file that has been read and parsed — independently of
+/// any others — but not yet added to the map.
+struct LoadedSource {
+	/// # Stem.
+	stem: String,
+
+	/// # Raw Source.
+	raw: String,
+
+	/// # (Provisional) ID.
+	///
+	/// With an ID template, this uses the shortest content hash, which may
+	/// need to be lengthened later on.
+	id: String,
+
+	/// # Parsed Symbol, Warnings, and Derived `viewBox` Flag.
+	parsed: (Symbol, ContentWarnings, bool),
+
+	/// # Modification Time.
+	mtime: Option<SystemTime>,
+
+	/// # Parse Time.
+	elapsed: Duration,
+}
+
+impl LoadedSource {
 	/// # New.
-	pub(super) fn new(
-		id: Option<&str>,
-		class: Option<&str>,
-		hide: HideType,
-		prefix: &str,
-		paths: &[PathBuf],
-	) -> Result<Self, SvgError> {
-		// There have to be paths.
-		if paths.is_empty() {
-			return Err(SvgError::NoSvgs);
+	///
+	/// Read and parse the file, coming up with an ID along the way.
+	fn new(opts: &MapOptions, path: &PathBuf) -> Result<Self, SvgError> {
+		// Load the SVG.
+		let raw: String = std::fs::read_to_string(path)
+			.map_err(|_| SvgError::Read(path.clone()))?;
+
+		// The symbol ID is built from the alphanumeric (and dash) characters
+		// in the file name, unless it has been remapped or declared.
+		let comment = if opts.read_id_comments { parse_id_comment(&raw) } else { None };
+		let stem = match path.file_stem().and_then(|s| opts.id_map.get(s.to_str()?)) {
+			Some(stem) => stem.clone(),
+			None => match comment {
+				Some(stem) if valid_stem(stem) => stem.to_owned(),
+				Some(stem) => return Err(SvgError::IdComment(path.clone(), stem.to_owned())),
+				None => parse_stem_id(path).ok_or_else(|| SvgError::FileName(path.clone()))?.into_owned(),
+			},
+		};
+
+		// Come up with an ID.
+		let id =
+			if let Some(tpl) = opts.id_template.as_deref() {
+				parse_id_template(tpl, &opts.prefix, path, &stem, &raw, opts.relaxed_ids, HASH_LEN_MIN)
+					.ok_or_else(|| SvgError::FileName(path.clone()))?
+			}
+			else {
+				opts.symbol_id(&stem)
+					.ok_or_else(|| SvgError::IdStart(path.clone(), stem.clone()))?
+			};
+
+		// Note the modification time, if we need it.
+		let mtime =
+			if matches!(opts.sort, SortMode::Mtime) {
+				std::fs::metadata(path).and_then(|m| m.modified()).ok()
+			}
+			else { None };
+
+		let now = Instant::now();
+		let parsed = parse_svg(&raw, path, &id, opts)?;
+		let elapsed = now.elapsed();
+
+		Ok(Self { stem, raw, id, parsed, mtime, elapsed })
+	}
+}
+
+
+
+/// # Map Builder.
+///
+/// This collects parsed symbols — from whatever source — and handles the
+/// final assembly of the map.
+struct MapBuilder<'a> {
+	/// # Options.
+	opts: &'a MapOptions,
+
+	/// # Symbols (in Insertion Order).
+	entries: Vec<(Symbol, MapSymbol)>,
+
+	/// # IDs.
+	///
+	/// This is used to detect duplicates.
+	ids: BTreeSet<String>,
+
+	/// # Warnings.
+	warnings: Vec<(PathBuf, ContentWarnings)>,
+
+	/// # Parse Times.
+	parse_times: Vec<(PathBuf, Duration)>,
+
+	/// # Input Bytes.
+	input_bytes: usize,
+}
+
+impl<'a> MapBuilder<'a> {
+	/// # New.
+	const fn new(opts: &'a MapOptions) -> Self {
+		Self {
+			opts,
+			entries: Vec::new(),
+			ids: BTreeSet::new(),
+			warnings: Vec::new(),
+			parse_times: Vec::new(),
+			input_bytes: 0,
+		}
+	}
+
+	/// # Contains ID?
+	fn contains(&self, id: &str) -> bool { self.ids.contains(id) }
+
+	/// # Push.
+	///
+	/// Parse a raw image and add it to the set under the given ID.
+	fn push(&mut self, id: String, path: &Path, raw: &str, mtime: Option<SystemTime>)
+	-> Result<(), SvgError> {
+		// Build up the symbol.
+		let (s, warn, derived_viewbox) = parse_svg(raw, path, &id, self.opts)?;
+		self.push_symbol(id, path, raw, s, warn, derived_viewbox, mtime)
+	}
+
+	/// # Push (Parsed) Symbol.
+	///
+	/// Finish processing an already-parsed symbol — see [`parse_svg`] — and add
+	/// it to the set under the given ID.
+	///
+	/// The raw source is only consulted for diagnostics concerning the root
+	/// element, which doesn't survive parsing.
+	#[expect(clippy::too_many_arguments, reason = "It is what it is.")]
+	fn push_symbol(
+		&mut self,
+		id: String,
+		path: &Path,
+		raw: &str,
+		mut s: Symbol,
+		warn: ContentWarnings,
+		derived_viewbox: bool,
+		mtime: Option<SystemTime>,
+	) -> Result<(), SvgError> {
+		// Make sure the ID isn't too long. Every symbol — split or not, from
+		// disk or memory — passes through here.
+		if let Some(max) = self.opts.max_id_length {
+			if max.get() < id.len() { return Err(SvgError::IdLength(id, max)); }
+		}
+
+		// Trim the viewBox precision?
+		if let Some(precision) = self.opts.precision {
+			let rounded = Element::get_attributes(&s).get("viewBox")
+				.and_then(|vb| round_viewbox(vb, precision).map(|new| (vb.to_string(), new)));
+			if let Some((old, new)) = rounded {
+				log::debug(format_args!("{id}: rounded the viewBox from {old} to {new}."));
+				s.assign("viewBox", new);
+			}
+		}
+
+		// Snap the viewBox to whole numbers?
+		let rounded_viewbox =
+			if self.opts.integer_viewbox {
+				Element::get_attributes(&s).get("viewBox")
+					.and_then(|vb| integer_viewbox(vb).map(|new| (vb.to_string(), new)))
+					.map(|(old, new)| {
+						log::debug(format_args!("{id}: rounded the viewBox from {old} to {new}."));
+						s.assign("viewBox", new);
+						old
+					})
+			}
+			else { None };
+
+		// Recolor with currentColor?
+		if self.opts.mono { mono_colors(&mut s); }
+
+		// Swap multiple colors for custom properties?
+		if self.opts.themable { themable(&mut s, &id); }
+
+		// Collect the colors, fonts, strokes, control characters, and
+		// invisible shapes, as requested.
+		let colors = collect(self.opts.report_colors, &s, colors);
+		let fonts = collect(self.opts.report_fonts, &s, fonts);
+		let strokes = collect(self.opts.report_strokes, &s, strokes);
+		let control_chars = collect(self.opts.report_control_chars, &s, control_chars);
+		let zero_shapes = collect(self.opts.warn_zero_shapes, &s, zero_shapes);
+
+		// Look for leftover namespace declarations?
+		let unused_namespaces =
+			if self.opts.report_unused_namespaces { unused_namespaces(&s) }
+			else { Vec::new() };
+
+		// Look for pixel-sized roots?
+		let fixed_dimensions =
+			if self.opts.warn_fixed_dimensions { fixed_dimensions(raw) }
+			else { None };
+
+		// Make sure the title (if any) matches the file name?
+		let title_mismatch =
+			if self.opts.warn_title_mismatch {
+				path.file_stem()
+					.and_then(|stem| {
+						let title = symbol_title(&s)?;
+						if title_matches(&title, &stem.to_string_lossy()) { None }
+						else { Some(title) }
+					})
+			}
+			else { None };
+
+		// Push it to temporary storage.
+		let info = MapSymbol {
+			viewbox: Element::get_attributes(&s).get("viewBox")
+				.map_or_else(String::new, ToString::to_string),
+			id: id.clone(),
+			src: path.to_path_buf(),
+			colors,
+			control_chars,
+			derived_viewbox,
+			fixed_dimensions,
+			fonts,
+			empty: Element::get_children(&s).iter()
+				.all(|c| matches!(c.get_name(), "desc" | "metadata" | "title")),
+			mtime,
+			rounded_viewbox,
+			strokes,
+			title_mismatch,
+			unused_namespaces,
+			warnings: warn,
+			zero_shapes,
+		};
+
+		// Expose the dimensions as data attributes?
+		if self.opts.emit_dimensions {
+			if let Some((w, h)) = info.dimensions() {
+				s.assign("data-width", w);
+				s.assign("data-height", h);
+			}
+		}
+
+		// Add a shared class, merging it with any kept from the source.
+		if let Some(class) = self.opts.symbol_class.as_deref() {
+			let merged = match Element::get_attributes(&s).get("class") {
+				Some(old) if old.split_ascii_whitespace().any(|c| c == class) => None,
+				Some(old) if ! old.trim().is_empty() => Some(format!("{} {class}", old.trim())),
+				_ => Some(class.to_owned()),
+			};
+			if let Some(merged) = merged { s.assign("class", merged); }
+		}
+		if ! self.ids.insert(id.clone()) { return Err(SvgError::Duplicate(id)); }
+		self.entries.push((s, info));
+
+		// Note if this has styles or other issues.
+		// (Split sources only need to be mentioned once.)
+		if ! warn.is_empty() && self.warnings.last().is_none_or(|(p, _)| p != path) {
+			self.warnings.push((path.to_path_buf(), warn));
+		}
+
+		Ok(())
+	}
+
+	/// # Finish.
+	///
+	/// Assemble the map, or return an error if any of the content warnings
+	/// have been promoted to errors.
+	fn finish(self) -> Result<Map, SvgError> {
+		let Self { opts, mut entries, mut warnings, parse_times, input_bytes, .. } = self;
+		warnings.sort_unstable_by(|a, b| a.0.file_name().cmp(&b.0.file_name()));
+
+		// Abort if any of the warnings have been promoted to errors.
+		let error_on =
+			if opts.strict { ContentWarnings::ALL }
+			else { opts.error_on };
+		let disallowed: Vec<(PathBuf, ContentWarnings)> = warnings.iter()
+			.filter_map(|(p, w)| {
+				let w = *w & error_on;
+				if w.is_empty() { None }
+				else { Some((p.clone(), w)) }
+			})
+			.collect();
+		if ! disallowed.is_empty() {
+			return Err(SvgError::Disallowed(disallowed));
+		}
+
+		// Strict mode also rejects the findings of the optional diagnostics.
+		if opts.strict {
+			let issues = strict_issues(&entries);
+			if ! issues.is_empty() { return Err(SvgError::StrictWarnings(issues)); }
 		}
 
 		// Start the map!
@@ -101,13 +721,16 @@ impl Map {
 			.set("aria-hidden", "true");
 
 		// Add an ID?
-		if let Some(id) = id { map = map.set("id", id); }
+		if let Some(id) = opts.id.as_deref() { map = map.set("id", id); }
 
 		// Add a class?
-		if let Some(class) = class { map = map.set("class", class); }
+		if let Some(class) = opts.class.as_deref() { map = map.set("class", class); }
 
 		// Hide it in some way?
-		match hide {
+		match opts.hide {
+			HideType::Display => {
+				map = map.set("style", "display:none");
+			},
 			HideType::Hidden => {
 				map = map.set("hidden", "true");
 			},
@@ -117,367 +740,4695 @@ impl Map {
 			HideType::None => {},
 		}
 
-		// Handle the paths!
-		let mut warned: Vec<Cow<str>> = Vec::new();
-		let len: usize = paths.len();
-		let mut nice_paths: BTreeMap<Cow<str>, Symbol> = BTreeMap::default();
-		for path in paths {
-			// The symbol ID is built from the alphanumeric (and dash)
-			// characters in the file name.
-			let stem = parse_stem_id(path)
-				.ok_or_else(|| SvgError::FileName(path.clone()))?;
-
-			// Build up the symbol.
-			let (s, warn) = parse_as_symbol(path, &stem, prefix)?;
-
-			// Push it to temporary storage.
-			if nice_paths.insert(stem.clone(), s).is_some() {
-				return Err(SvgError::Duplicate(stem.into_owned()));
-			}
-
-			// Note if this has styles or other issues.
-			if warn {
-				if let Some(name) = path.file_name() {
-					// TODO: keep name as-is; use .display for printing once stable.
-					warned.push(name.to_string_lossy());
+		// Reorder the symbols.
+		log::debug(format_args!(
+			"Sorting {} symbol{} by {}.",
+			entries.len(),
+			if entries.len() == 1 { "" } else { "s" },
+			opts.sort.as_str(),
+		));
+		match opts.sort {
+			SortMode::Alpha => { entries.sort_unstable_by(|a, b| a.1.id.cmp(&b.1.id)); },
+			SortMode::AlphaDesc => { entries.sort_unstable_by(|a, b| b.1.id.cmp(&a.1.id)); },
+			SortMode::File => {},
+			SortMode::Mtime => {
+				entries.sort_unstable_by(|a, b| a.1.id.cmp(&b.1.id));
+				if ! sort_mtime(&mut entries) {
+					Msg::info("The modification times are all identical; falling back to alphabetical order.")
+						.eprint();
 				}
-			}
+			},
+			SortMode::Usage => {
+				let usage = |s: &MapSymbol| opts.usage.get(&s.id).copied();
+				entries.sort_unstable_by(|a, b|
+					usage(&b.1).cmp(&usage(&a.1)).then_with(|| a.1.id.cmp(&b.1.id))
+				);
+			},
 		}
 
-		// Mention any potential style/class issues.
-		if ! warned.is_empty() {
-			Msg::warning(format!(
-				"Scripts, styles, classes, and IDs may not work correctly in sprite map
-contexts; the following image{} might need to be refactored:",
-				if len == 1 { "" } else { "s" },
-			))
-				.eprint();
+		// We can add the children on-the-fly.
+		let len = entries.len();
+		let mut symbols = Vec::with_capacity(len);
+		let inner = entries.into_iter().fold(map, |m, (s, info)| {
+			symbols.push(info);
+			m.add(s)
+		});
 
-			warned.sort_unstable();
-			for w in warned {
-				eprintln!("    \x1b[1;93m•\x1b[0m {w}");
-			}
-		}
+		let out = Map {
+			inner,
+			hide: opts.hide,
+			mode: opts.mode,
+			attribute_order: opts.attribute_order.clone(),
+			self_closing: opts.self_closing,
+			doctype: opts.doctype,
+			html_fragment: opts.html_fragment,
+			len,
+			symbols,
+			warnings,
+			parse_times,
+			input_bytes,
+		};
+
+		// Mention any potential style/class issues.
+		if opts.warnings_summary { crate::report::print_warning_summary(&out); }
+		else { out.print_warnings(); }
 
 		// Done!
-		Ok(Self {
-			// We can add the children on-the-fly.
-			inner: nice_paths.into_iter().fold(map, |m, (_, s)| m.add(s)),
-			hide,
-			len,
-		})
+		Ok(out)
 	}
-
-	/// # Length.
-	///
-	/// Return the number of children (`symbol` elements).
-	pub(super) const fn len(&self) -> usize { self.len }
 }
 
 
 
-#[derive(Debug, Clone, Copy)]
-/// # Map Hiding Strategy.
+#[derive(Debug, Clone)]
+/// # Symbol Details.
 ///
-/// SVG maps aren't generally intended for direct display. This enum holds the
-/// different strategies for keeping it that way.
-pub(super) enum HideType {
-	/// # Don't Hide.
-	None,
+/// This holds the ID, source, and other metadata for a symbol in the map.
+pub(super) struct MapSymbol {
+	/// # ID.
+	id: String,
 
-	/// # Hide with `hidden` Attribute.
-	Hidden,
+	/// # Source Path.
+	src: PathBuf,
 
-	/// # Position Offscreen.
-	Offscreen,
-}
+	/// # `viewBox`.
+	viewbox: String,
 
+	/// # Colors.
+	///
+	/// This holds the (normalized) value of every color-valued attribute or
+	/// style declaration, if they were looked for.
+	colors: Vec<String>,
 
+	/// # Control Character Locations.
+	///
+	/// This holds the `<tag> attribute` (or `<tag> text`) locations of any
+	/// stray control characters, if they were looked for.
+	control_chars: Vec<String>,
 
-/// # Is Empty Element?
-fn is_empty(src: &Element) -> bool {
-	src.get_attributes().is_empty() &&
-	src.get_children().is_empty() &&
-	matches!(
-		src.get_name(),
-		"a" | "defs" | "glyph" | "g" | "marker" | "mask" | "missing-glyph" |
-		"pattern" | "script" | "style" | "switch"
-	)
-}
+	/// # Derived `viewBox`?
+	///
+	/// This is `true` if the source had no `viewBox` of its own and one had to
+	/// be synthesized from its `width` and `height`.
+	derived_viewbox: bool,
 
-/// # Check for Styles, Classes, IDs.
-///
-/// Styles, classes, and IDs inside of SVGs have a habit of colliding with one
-/// another, particularly in map contexts. This method looks to see if there
-/// are any so we can issue a warning.
-fn has_styles(src: &[Event]) -> bool {
-	src.iter()
-		.filter_map(|e|
-			if let Event::Tag(name, _, attrs) = e { Some((name, attrs)) }
-			else { None }
-		)
-		.any(|(name, attrs)|
-			match name.len() {
-				5 => name.eq_ignore_ascii_case("style"),
-				6 => name.eq_ignore_ascii_case("script"),
-				_ => false,
-			} ||
-			attrs.keys().any(|k|
-				match k.len() {
-					2 => k.eq_ignore_ascii_case("id"),
-					5 => k.eq_ignore_ascii_case("class") || k.eq_ignore_ascii_case("style"),
-					_ => k.starts_with("on"),
-				}
-			)
-		)
-}
+	/// # Fixed Dimensions.
+	///
+	/// This holds the source root's `width`/`height` if they carry absolute
+	/// units alongside a `viewBox`, if checked.
+	fixed_dimensions: Option<String>,
 
-/// # Parse SVG into Symbol.
-///
-/// This parses and somewhat validates an input SVG, returning it as a `Symbol`
-/// suitable for inclusion in the map.
-fn parse_as_symbol(path: &Path, stem: &str, prefix: &str)
--> Result<(Symbol, bool), SvgError> {
-	// Load the SVG. We'll do this as bytes for now.
-	let raw: String = std::fs::read_to_string(path)
-		.map_err(|_| SvgError::Read(path.to_path_buf()))?;
+	/// # Fonts.
+	///
+	/// This holds the `font-family` values used in each attribute and `style`
+	/// declaration, if they were looked for.
+	fonts: Vec<String>,
 
-	// Find the start and end ranges.
-	let (start, end) = ranges(raw.as_bytes()).ok_or_else(|| SvgError::Parse(path.to_path_buf()))?;
+	/// # Empty?
+	///
+	/// This is `true` if the symbol has no content beyond (maybe) a title,
+	/// description, or metadata, i.e. nothing to render.
+	empty: bool,
 
-	// Parse it.
-	let mut events: Vec<Event> = Vec::new();
-	for event in Parser::new(&raw[start..end]) {
-		match event {
-			Event::Error(_) => return Err(SvgError::Parse(path.to_path_buf())),
-			Event::Tag(_, _, _) | Event::Text(_) => { events.push(event); },
-			_ => {},
-		}
-	}
+	/// # Source Modification Time.
+	///
+	/// This is only populated when sorting by modification time.
+	mtime: Option<SystemTime>,
 
-	// The last event should be a closing SVG tag.
-	match events.pop() {
-		Some(Event::Tag(s, Type::End, _)) if s.eq_ignore_ascii_case("svg") => {},
-		_ => return Err(SvgError::Parse(path.to_path_buf())),
+	/// # Original `viewBox`.
+	///
+	/// This holds the original `viewBox` if fractional dimensions were rounded
+	/// to whole numbers.
+	rounded_viewbox: Option<String>,
+
+	/// # Unscaled Strokes.
+	///
+	/// This holds the tag names of any elements with a stroke but no
+	/// `vector-effect`, if they were looked for.
+	strokes: Vec<String>,
+
+	/// # Mismatched Title.
+	///
+	/// This holds the symbol's `<title>` if it doesn't seem to match the
+	/// source file name, if checked.
+	title_mismatch: Option<String>,
+
+	/// # Unused Namespaces.
+	///
+	/// This holds any `xmlns:*` prefixes declared within the symbol but not
+	/// used by any of its elements or attributes, if they were looked for.
+	unused_namespaces: Vec<String>,
+
+	/// # Content Warnings.
+	warnings: ContentWarnings,
+
+	/// # Zero-Dimension Shapes.
+	///
+	/// This holds the `<tag> attribute` locations of any shapes with
+	/// zero-valued dimensions, if they were looked for.
+	zero_shapes: Vec<String>,
+}
+
+impl MapSymbol {
+	/// # ID.
+	pub(super) fn id(&self) -> &str { &self.id }
+
+	/// # Source Path.
+	pub(super) fn src(&self) -> &Path { &self.src }
+
+	/// # `viewBox`.
+	pub(super) fn viewbox(&self) -> &str { &self.viewbox }
+
+	/// # Colors.
+	pub(super) fn colors(&self) -> &[String] { &self.colors }
+
+	/// # Control Character Locations.
+	pub(super) fn control_chars(&self) -> &[String] { &self.control_chars }
+
+	/// # Derived `viewBox`?
+	pub(super) const fn derived_viewbox(&self) -> bool { self.derived_viewbox }
+
+	/// # Dimensions.
+	///
+	/// Return the width and height from the `viewBox`, if parseable.
+	pub(super) fn dimensions(&self) -> Option<(f64, f64)> {
+		let mut parts = self.viewbox.split(|c: char| c == ',' || c.is_ascii_whitespace())
+			.filter(|p| ! p.is_empty())
+			.skip(2);
+		let w = parts.next()?.parse::<f64>().ok()?;
+		let h = parts.next()?.parse::<f64>().ok()?;
+		Some((w, h))
+	}
+
+	/// # Fixed Dimensions.
+	pub(super) fn fixed_dimensions(&self) -> Option<&str> { self.fixed_dimensions.as_deref() }
+
+	/// # Fonts.
+	pub(super) fn fonts(&self) -> &[String] { &self.fonts }
+
+	/// # Original `viewBox`.
+	pub(super) fn rounded_viewbox(&self) -> Option<&str> { self.rounded_viewbox.as_deref() }
+
+	/// # Empty?
+	pub(super) const fn empty(&self) -> bool { self.empty }
+
+	/// # Unscaled Strokes.
+	pub(super) fn strokes(&self) -> &[String] { &self.strokes }
+
+	/// # Mismatched Title.
+	pub(super) fn title_mismatch(&self) -> Option<&str> { self.title_mismatch.as_deref() }
+
+	/// # Unused Namespaces.
+	pub(super) fn unused_namespaces(&self) -> &[String] { &self.unused_namespaces }
+
+	/// # Content Warnings.
+	pub(super) const fn warnings(&self) -> ContentWarnings { self.warnings }
+
+	/// # Zero-Dimension Shapes.
+	pub(super) fn zero_shapes(&self) -> &[String] { &self.zero_shapes }
+}
+
+
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # Output Style.
+pub(super) enum OutputStyle {
+	#[default]
+	/// # Compact.
+	///
+	/// Everything on one line, but otherwise as-is.
+	Compact,
+
+	/// # Pretty.
+	///
+	/// One element per line, indented with tabs. Elements containing any text
+	/// are kept on a single line so the indentation doesn't become part of
+	/// the content.
+	Pretty,
+
+	/// # Minified.
+	///
+	/// Compact, with the whitespace runs within attribute values collapsed to
+	/// a single space. Text, `<script>`, `<style>`, and `xml:space="preserve"`
+	/// content is left as-is.
+	Minified,
+}
+
+
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # Map Hiding Strategy.
+///
+/// SVG maps aren't generally intended for direct display. This enum holds the
+/// different strategies for keeping it that way.
+pub(super) enum HideType {
+	/// # Don't Hide.
+	None,
+
+	/// # Hide with `display: none`.
+	///
+	/// Note that some older browsers won't paint `<use>` references to
+	/// symbols within a map hidden this way.
+	Display,
+
+	/// # Hide with `hidden` Attribute.
+	Hidden,
+
+	/// # Position Offscreen.
+	Offscreen,
+}
+
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # Symbol Sort Order.
+pub(super) enum SortMode {
+	#[default]
+	/// # Alphabetical (by ID).
+	Alpha,
+
+	/// # Reverse Alphabetical (by ID).
+	AlphaDesc,
+
+	/// # File Order.
+	///
+	/// Symbols are left in the order their sources were given.
+	File,
+
+	/// # Modification Time (Oldest First).
+	///
+	/// Ties are broken alphabetically.
+	Mtime,
+
+	/// # Usage (Most Used First).
+	///
+	/// Ties — and any symbols without usage data — are sorted alphabetically,
+	/// the latter at the end.
+	Usage,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # Self-Closing Style.
+///
+/// This determines how childless elements are serialized.
+pub(super) enum SelfClosing {
+	#[default]
+	/// # Always (e.g. `<path/>`).
+	Always,
+
+	/// # Never (e.g. `<path></path>`).
+	Never,
+}
+
+impl OutputStyle {
+	/// # From Name.
+	///
+	/// Return the style corresponding to the (CLI) name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"compact" => Some(Self::Compact),
+			"minified" => Some(Self::Minified),
+			"pretty" => Some(Self::Pretty),
+			_ => None,
+		}
+	}
+}
+
+impl SelfClosing {
+	/// # From Name.
+	///
+	/// Return the style corresponding to the (CLI) name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"always" => Some(Self::Always),
+			"never" => Some(Self::Never),
+			_ => None,
+		}
+	}
+
+	/// # Close Empty.
+	///
+	/// Close out an element with no children.
+	fn close_empty(self, name: &str, out: &mut String) {
+		match self {
+			Self::Always => { out.push_str("/>"); },
+			Self::Never => {
+				out.push_str("></");
+				out.push_str(name);
+				out.push('>');
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # `viewBox` Source.
+///
+/// This determines where each symbol's `viewBox` comes from.
+pub(super) enum ViewboxSource {
+	#[default]
+	/// # Automatic.
+	///
+	/// Use the root `viewBox`, falling back to the `width` and `height`.
+	Auto,
+
+	/// # `viewBox` Only.
+	Viewbox,
+
+	/// # `width` and `height` Only.
+	WidthHeight,
+}
+
+impl ViewboxSource {
+	/// # From Name.
+	///
+	/// Return the source corresponding to the (CLI) name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"auto" => Some(Self::Auto),
+			"viewbox" => Some(Self::Viewbox),
+			"width-height" => Some(Self::WidthHeight),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # Sprite Mode.
+///
+/// This determines how the individual images are wrapped within the map.
+pub(super) enum SpriteMode {
+	#[default]
+	/// # Symbols.
+	///
+	/// Each image is a `<symbol>` with its own `viewBox`.
+	Symbol,
+
+	/// # Definitions.
+	///
+	/// Each image is a `<g>` within a single `<defs>`. As groups have no
+	/// `viewBox` of their own, any offset is applied via `transform`
+	/// instead.
+	Defs,
+}
+
+impl HideType {
+	/// # From Name.
+	///
+	/// Return the strategy corresponding to the (CLI) name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"display" => Some(Self::Display),
+			"hidden" => Some(Self::Hidden),
+			"none" => Some(Self::None),
+			"offscreen" => Some(Self::Offscreen),
+			_ => None,
+		}
+	}
+}
+
+impl SpriteMode {
+	/// # From Name.
+	///
+	/// Return the mode corresponding to the (CLI) name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"defs" => Some(Self::Defs),
+			"symbol" => Some(Self::Symbol),
+			_ => None,
+		}
+	}
+}
+
+impl SortMode {
+	/// # From Name.
+	///
+	/// Return the mode corresponding to the (CLI) name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"alpha" => Some(Self::Alpha),
+			"alpha-desc" => Some(Self::AlphaDesc),
+			"file" => Some(Self::File),
+			"mtime" => Some(Self::Mtime),
+			"usage" => Some(Self::Usage),
+			_ => None,
+		}
+	}
+
+	/// # As Str.
+	///
+	/// Return the (CLI) name of the mode.
+	pub(super) const fn as_str(self) -> &'static str {
+		match self {
+			Self::Alpha => "alpha",
+			Self::AlphaDesc => "alpha-desc",
+			Self::File => "file",
+			Self::Mtime => "mtime",
+			Self::Usage => "usage",
+		}
+	}
+}
+
+
+
+/// # Expired?
+///
+/// Returns `true` if there is a deadline and it has passed.
+fn expired(deadline: Option<Instant>) -> bool {
+	deadline.is_some_and(|d| d <= Instant::now())
+}
+
+/// # Integer `viewBox`.
+///
+/// If the `viewBox` width or height is fractional, return a copy with both
+/// rounded to whole numbers (no smaller than one). The `x` and `y` values are
+/// left as-is.
+///
+/// Returns `None` if the `viewBox` is already whole, or can't be parsed.
+fn integer_viewbox(src: &str) -> Option<String> {
+	let mut parts = src.split(|c: char| c == ',' || c.is_ascii_whitespace())
+		.filter(|p| ! p.is_empty());
+	let x = parts.next()?;
+	let y = parts.next()?;
+	let w = parts.next()?.parse::<f64>().ok()?;
+	let h = parts.next()?.parse::<f64>().ok()?;
+	if parts.next().is_some() || (w.fract() == 0.0 && h.fract() == 0.0) { return None; }
+
+	Some(format!("{x} {y} {} {}", w.round().max(1.0), h.round().max(1.0)))
+}
+
+/// # Round `viewBox`.
+///
+/// Return a copy of the `viewBox` with its width and height rounded to
+/// `precision` decimal places (trailing zeros trimmed), leaving the `x` and
+/// `y` values as-is. A dimension that would round down to zero keeps its
+/// original value instead.
+///
+/// Returns `None` if nothing would change, or the `viewBox` can't be parsed.
+fn round_viewbox(src: &str, precision: u8) -> Option<String> {
+	/// # Round.
+	fn round(raw: &str, precision: u8) -> Option<String> {
+		let old = raw.parse::<f64>().ok()?;
+		let mut new = format!("{old:.*}", usize::from(precision));
+		if new.contains('.') {
+			new.truncate(new.trim_end_matches('0').trim_end_matches('.').len());
+		}
+
+		// Keep the original if rounding would leave nothing.
+		if new.parse::<f64>().ok().is_some_and(|n| 0.0 < n) { Some(new) }
+		else { Some(raw.to_owned()) }
+	}
+
+	let mut parts = src.split(|c: char| c == ',' || c.is_ascii_whitespace())
+		.filter(|p| ! p.is_empty());
+	let x = parts.next()?;
+	let y = parts.next()?;
+	let w_raw = parts.next()?;
+	let h_raw = parts.next()?;
+	if parts.next().is_some() { return None; }
+
+	let w = round(w_raw, precision)?;
+	let h = round(h_raw, precision)?;
+	if w == w_raw && h == h_raw { None }
+	else { Some(format!("{x} {y} {w} {h}")) }
+}
+
+/// # Defs Root.
+///
+/// Return a copy of the map with its `<symbol>` children converted to `<g>`
+/// elements and wrapped in a single `<defs>`. (Anything else is moved into
+/// the `<defs>` as-is.)
+fn defs_root(src: &SVG) -> SVG {
+	let mut defs = Element::new("defs");
+	for child in Element::get_children(src) {
+		if child.get_name() == "symbol" { defs.append(symbol_to_g(child.as_ref())); }
+		else { defs.get_children_mut().push(child.clone()); }
+	}
+
+	let mut out = SVG::new();
+	Element::get_attributes_mut(&mut out).clone_from(Element::get_attributes(src));
+	out.append(defs);
+	out
+}
+
+/// # Symbol to Group.
+///
+/// Convert a `<symbol>` to an equivalent `<g>`, keeping its attributes and
+/// children, save for `viewBox` and `preserveAspectRatio`, which groups don't
+/// support. A non-zero `viewBox` origin is applied as a `translate` instead,
+/// so the content starts at `0,0` like it would within a symbol.
+fn symbol_to_g(src: &dyn Node) -> Element {
+	let mut g = Element::new("g");
+	let mut transform = None;
+	if let Some(attrs) = src.get_attributes() {
+		for (k, v) in attrs {
+			match k.as_str() {
+				"viewBox" => { transform = viewbox_translate(v); },
+				"preserveAspectRatio" => {},
+				_ => { g.assign(k.as_str(), v.clone()); },
+			}
+		}
+	}
+
+	// Merge the translation with any existing transform.
+	if let Some(mut transform) = transform {
+		if let Some(old) = g.get_attributes().get("transform") {
+			transform.push(' ');
+			transform.push_str(old);
+		}
+		g.assign("transform", transform);
+	}
+
+	if let Some(children) = src.get_children() {
+		g.get_children_mut().extend(children.iter().cloned());
+	}
+	g
+}
+
+/// # `viewBox` Translation.
+///
+/// Return a `translate` transform undoing the `viewBox` origin, or `None` if
+/// it is already `0,0` (or can't be parsed).
+fn viewbox_translate(src: &str) -> Option<String> {
+	let mut parts = src.split(|c: char| c == ',' || c.is_ascii_whitespace())
+		.filter(|p| ! p.is_empty());
+	let x = parts.next()?.parse::<f64>().ok()?;
+	let y = parts.next()?.parse::<f64>().ok()?;
+	if x == 0.0 && y == 0.0 { None }
+	// Adding zero keeps -0 from creeping in.
+	else { Some(format!("translate({} {})", -x + 0.0, -y + 0.0)) }
+}
+
+/// # Is Empty Element?
+///
+/// Returns `true` for attribute- and child-less elements that serve no
+/// purpose on their own, unless the user has asked for that tag to be kept.
+fn is_empty(src: &Element, opts: &MapOptions) -> bool {
+	src.get_attributes().is_empty() &&
+	src.get_children().is_empty() &&
+	! opts.keep_empty.iter().any(|t| t.eq_ignore_ascii_case(src.get_name())) &&
+	matches!(
+		src.get_name(),
+		"a" | "defs" | "glyph" | "g" | "marker" | "mask" | "missing-glyph" |
+		"pattern" | "script" | "style" | "switch"
+	)
+}
+
+/// # Clean Attribute Whitespace.
+///
+/// Recursively collapse whitespace runs within attribute values — aside from
+/// any inside quoted strings — to single spaces, trimming the ends.
+fn clean_attr_whitespace(src: &mut dyn Node) {
+	if let Some(attrs) = src.get_attributes_mut() {
+		for v in attrs.values_mut() {
+			if let Some(new) = collapse_whitespace(v) { *v = Value::from(new); }
+		}
+	}
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			clean_attr_whitespace(child.as_mut());
+		}
+	}
+}
+
+/// # Minify Attribute Whitespace.
+///
+/// Like [`clean_attr_whitespace`], but for [`OutputStyle::Minified`], leaving
+/// `<script>` and `<style>` elements — and anything marked
+/// `xml:space="preserve"` — alone, along with their descendants.
+fn minify_attr_whitespace(src: &mut dyn Node) {
+	if matches!(src.get_name(), "script" | "style") { return; }
+	if let Some(attrs) = src.get_attributes_mut() {
+		if attrs.get("xml:space").is_some_and(|v| v.trim() == "preserve") { return; }
+		for v in attrs.values_mut() {
+			if let Some(new) = collapse_whitespace(v) { *v = Value::from(new); }
+		}
+	}
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			minify_attr_whitespace(child.as_mut());
+		}
+	}
+}
+
+/// # Trim Path Whitespace.
+///
+/// Recursively tidy the whitespace in `d` and `points` attribute values — see
+/// [`trim_path`] — leaving everything else as-is.
+fn trim_path_whitespace(src: &mut dyn Node) {
+	if let Some(attrs) = src.get_attributes_mut() {
+		for (k, v) in attrs.iter_mut() {
+			if matches!(k.as_str(), "d" | "points") {
+				if let Some(new) = trim_path(v) { *v = Value::from(new); }
+			}
+		}
+	}
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			trim_path_whitespace(child.as_mut());
+		}
+	}
+}
+
+/// # Trim Path.
+///
+/// Collapse whitespace runs to single spaces, drop any around commas, and
+/// trim the ends, e.g. `M 0 , 0  L 10  10` becomes `M 0,0 L 10 10`. Nothing
+/// else is touched.
+///
+/// Returns `None` if nothing would change.
+fn trim_path(src: &str) -> Option<String> {
+	let mut out = String::with_capacity(src.len());
+	for (idx, part) in src.split(',').enumerate() {
+		if idx != 0 { out.push(','); }
+		for (idx, word) in part.split_whitespace().enumerate() {
+			if idx != 0 { out.push(' '); }
+			out.push_str(word);
+		}
+	}
+
+	if out == src { None }
+	else { Some(out) }
+}
+
+/// # Minify Path Data.
+///
+/// Recursively rewrite `d` and `points` attribute values as compactly as
+/// possible — see [`minify_path`] — leaving any that can't be parsed as-is.
+fn minify_paths(src: &mut dyn Node) {
+	if let Some(attrs) = src.get_attributes_mut() {
+		for (k, v) in attrs.iter_mut() {
+			let new = match k.as_str() {
+				"d" => minify_path(v, true),
+				"points" => minify_path(v, false),
+				_ => None,
+			};
+			if let Some(new) = new { *v = Value::from(new); }
+		}
+	}
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			minify_paths(child.as_mut());
+		}
+	}
+}
+
+/// # Minify Path.
+///
+/// Rejoin the path data — or with `commands` disabled, a `points` list —
+/// using as few separators as possible and dropping leading zeros, e.g.
+/// `M 0.5, 10 L -0.25 3` becomes `M.5 10L-.25 3`.
+///
+/// The numbers themselves are otherwise kept verbatim, so the geometry is
+/// unchanged. `None` is returned if the data can't be parsed.
+fn minify_path(src: &str, commands: bool) -> Option<String> {
+	let mut out = String::with_capacity(src.len());
+	let mut last: Option<bool> = None; // Whether the last number was a fraction.
+	for token in path_tokens(src, commands)? {
+		// Commands never need separating.
+		if token.starts_with(|c: char| c.is_ascii_alphabetic()) {
+			out.push_str(token);
+			last = None;
+			continue;
+		}
+
+		// Drop the leading zero from fractions.
+		let (sign, num) = token.split_at(usize::from(token.starts_with(['-', '+'])));
+		let num = if num.starts_with("0.") && 2 < num.len() { &num[1..] } else { num };
+
+		// Numbers only need separating if the next one wouldn't end the
+		// last on its own, i.e. with a sign or a second decimal point.
+		if let Some(frac) = last {
+			if sign.is_empty() && ! (frac && num.starts_with('.')) { out.push(' '); }
+		}
+		out.push_str(sign);
+		out.push_str(num);
+		last.replace(num.contains('.') && ! num.contains(['e', 'E']));
+	}
+	Some(out)
+}
+
+/// # Path Tokens.
+///
+/// Split path data into its individual commands and numbers, or `None` if it
+/// contains anything unexpected. Arc flags, which may be written without
+/// separators (e.g. `a1 1 0 011 1`), are returned as single digits.
+///
+/// With `commands` disabled, only numbers are allowed (as in a `points`
+/// list).
+fn path_tokens(src: &str, commands: bool) -> Option<Vec<&str>> {
+	/// # Number Length.
+	///
+	/// Return the length of the number at the start of `src`, if any.
+	fn number(src: &[u8]) -> Option<usize> {
+		let mut pos = usize::from(matches!(src.first(), Some(b'-' | b'+')));
+		let digits = |pos: usize| src[pos..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+		let int = digits(pos);
+		pos += int;
+		let mut frac = 0;
+		if src.get(pos) == Some(&b'.') {
+			frac = digits(pos + 1);
+			pos += 1 + frac;
+		}
+		if int + frac == 0 { return None; }
+
+		// Exponent?
+		if matches!(src.get(pos), Some(b'e' | b'E')) {
+			let sign = usize::from(matches!(src.get(pos + 1), Some(b'-' | b'+')));
+			let exp = digits(pos + 1 + sign);
+			if exp != 0 { pos += 1 + sign + exp; }
+		}
+
+		Some(pos)
+	}
+
+	let bytes = src.as_bytes();
+	let mut out = Vec::new();
+	let mut cmd = None;
+	let mut arg = 0_usize;
+	let mut pos = 0;
+	while pos < bytes.len() {
+		let b = bytes[pos];
+		if b.is_ascii_whitespace() || b == b',' { pos += 1; }
+		else if b.is_ascii_alphabetic() {
+			if ! commands || ! b"MmLlHhVvCcSsQqTtAaZz".contains(&b) { return None; }
+			out.push(&src[pos..=pos]);
+			cmd.replace(b);
+			arg = 0;
+			pos += 1;
+		}
+		else {
+			let len = match cmd {
+				Some(b'Z' | b'z') => return None,
+				Some(b'A' | b'a') if matches!(arg % 7, 3 | 4) =>
+					if matches!(b, b'0' | b'1') { 1 } else { return None; },
+				None if commands => return None,
+				_ => number(&bytes[pos..])?,
+			};
+			out.push(&src[pos..pos + len]);
+			arg += 1;
+			pos += len;
+		}
+	}
+
+	Some(out)
+}
+
+/// # Collapse Whitespace.
+///
+/// Return a copy of `src` with whitespace runs collapsed to a single space and
+/// the ends trimmed, or `None` if nothing would change. Whitespace within
+/// single- or double-quoted substrings is left as-is.
+fn collapse_whitespace(src: &str) -> Option<String> {
+	let mut out = String::with_capacity(src.len());
+	let mut quote = None;
+	let mut ws = false;
+	for c in src.trim().chars() {
+		if quote.is_none() && c.is_ascii_whitespace() {
+			ws = true;
+			continue;
+		}
+
+		if ws {
+			out.push(' ');
+			ws = false;
+		}
+
+		match quote {
+			Some(q) if q == c => { quote = None; },
+			None if c == '"' || c == '\'' => { quote = Some(c); },
+			_ => {},
+		}
+		out.push(c);
+	}
+
+	if out == src { None }
+	else { Some(out) }
+}
+
+/// # Collect (If Enabled).
+///
+/// Run one of the recursive collectors — [`colors`], [`fonts`], etc. — over
+/// the symbol and return the results, or an empty list if not `enabled`.
+fn collect(enabled: bool, src: &Symbol, cb: fn(&dyn Node, &mut Vec<String>)) -> Vec<String> {
+	let mut out = Vec::new();
+	if enabled { cb(src, &mut out); }
+	out
+}
+
+/// # Collect Colors.
+///
+/// Recursively gather the (normalized) values of all color-related
+/// attributes and `style` declarations — `fill`, `stroke`, `stop-color`, etc.
+/// — onto `out`, one entry per occurrence.
+fn colors(src: &dyn Node, out: &mut Vec<String>) {
+	if let Some(attrs) = src.get_attributes() {
+		for (k, v) in attrs {
+			if COLOR_PROPS.iter().any(|p| k.eq_ignore_ascii_case(p)) {
+				out.extend(normalize_color(v));
+			}
+			else if k.eq_ignore_ascii_case("style") {
+				for (prop, value) in v.split(';').filter_map(|d| d.split_once(':')) {
+					if COLOR_PROPS.iter().any(|p| prop.trim().eq_ignore_ascii_case(p)) {
+						out.extend(normalize_color(value));
+					}
+				}
+			}
+		}
+	}
+
+	for child in src.get_children().map_or(&[][..], Vec::as_slice) {
+		colors(child.as_ref(), out);
+	}
+}
+
+/// # Collect Fonts.
+///
+/// Recursively gather the `font-family` values of all attributes and `style`
+/// declarations onto `out`, one entry per occurrence, with whitespace
+/// collapsed.
+fn fonts(src: &dyn Node, out: &mut Vec<String>) {
+	/// # Normalize.
+	fn normalize(src: &str) -> Option<String> {
+		let src = src.trim().trim_end_matches("!important").trim();
+		if src.is_empty() { None }
+		else { Some(src.split_ascii_whitespace().collect::<Vec<_>>().join(" ")) }
+	}
+
+	if let Some(attrs) = src.get_attributes() {
+		for (k, v) in attrs {
+			if k.eq_ignore_ascii_case("font-family") { out.extend(normalize(v)); }
+			else if k.eq_ignore_ascii_case("style") {
+				for (prop, value) in v.split(';').filter_map(|d| d.split_once(':')) {
+					if prop.trim().eq_ignore_ascii_case("font-family") {
+						out.extend(normalize(value));
+					}
+				}
+			}
+		}
+	}
+
+	for child in src.get_children().map_or(&[][..], Vec::as_slice) {
+		fonts(child.as_ref(), out);
+	}
+}
+
+/// # Collect Unscaled Strokes.
+///
+/// Recursively look for elements with a (non-`none`) `stroke` — attribute or
+/// `style` declaration — but no `vector-effect` of their own, recording each
+/// distinct tag name in `out`.
+fn strokes(src: &dyn Node, out: &mut Vec<String>) {
+	if let Some(attrs) = src.get_attributes() {
+		let mut stroke = false;
+		let mut effect = false;
+		for (k, v) in attrs {
+			if k.eq_ignore_ascii_case("stroke") { stroke |= ! is_none(v); }
+			else if k.eq_ignore_ascii_case("vector-effect") { effect = true; }
+			else if k.eq_ignore_ascii_case("style") {
+				for (prop, value) in v.split(';').filter_map(|d| d.split_once(':')) {
+					let prop = prop.trim();
+					if prop.eq_ignore_ascii_case("stroke") { stroke |= ! is_none(value); }
+					else if prop.eq_ignore_ascii_case("vector-effect") { effect = true; }
+				}
+			}
+		}
+
+		let name = src.get_name();
+		if stroke && ! effect && ! out.iter().any(|n| n == name) { out.push(name.to_owned()); }
+	}
+
+	for child in src.get_children().map_or(&[][..], Vec::as_slice) {
+		strokes(child.as_ref(), out);
+	}
+}
+
+/// # Is None?
+///
+/// Returns `true` if a paint value is `none` (or empty).
+fn is_none(src: &str) -> bool {
+	let src = src.trim().trim_end_matches("!important").trim();
+	src.is_empty() || src.eq_ignore_ascii_case("none")
+}
+
+/// # Check for Styles, Classes, IDs, Etc.
+///
+/// Styles, classes, and IDs inside of SVGs have a habit of colliding with one
+/// another, particularly in map contexts, and scripts and external resources
+/// may not load at all. This method looks to see if there are any so we can
+/// issue a warning.
+fn content_warnings(src: &[Event]) -> ContentWarnings {
+	let mut out = ContentWarnings::NONE;
+	for (name, attrs) in src.iter().filter_map(|e|
+		if let Event::Tag(name, _, attrs) = e { Some((name, attrs)) }
+		else { None }
+	) {
+		// Check the tag.
+		if name.eq_ignore_ascii_case("style") { out |= ContentWarnings::STYLES; }
+		else if name.eq_ignore_ascii_case("script") { out |= ContentWarnings::SCRIPTS; }
+		else if name.eq_ignore_ascii_case("image") { out |= ContentWarnings::RASTERS; }
+
+		// Check the attributes.
+		for (k, v) in attrs {
+			if k.eq_ignore_ascii_case("id") { out |= ContentWarnings::IDS; }
+			else if k.eq_ignore_ascii_case("class") { out |= ContentWarnings::CLASSES; }
+			else if k.eq_ignore_ascii_case("style") { out |= ContentWarnings::INLINE_STYLES; }
+			else if k.starts_with("on") { out |= ContentWarnings::SCRIPTS; }
+			else if k.eq_ignore_ascii_case("href") || k.eq_ignore_ascii_case("xlink:href") {
+				let v = v.trim();
+				if v.starts_with("data:") { out |= ContentWarnings::DATA_URIS; }
+				else if ! v.starts_with('#') { out |= ContentWarnings::EXTERNAL_REFS; }
+			}
+		}
+	}
+
+	out
+}
+
+/// # Has Text?
+///
+/// Returns `true` if a `<text>`, `<tspan>`, or `<textPath>` within the node
+/// contains any (non-whitespace) text.
+fn has_text(src: &dyn Node) -> bool {
+	let children = src.get_children().map_or(&[][..], Vec::as_slice);
+	if matches!(src.get_name(), "text" | "textpath" | "tspan") && children.iter().any(|c|
+		c.get_attributes().is_none() && ! c.to_string().trim().is_empty()
+	) {
+		return true;
+	}
+
+	children.iter().any(|c| c.get_attributes().is_some() && has_text(c.as_ref()))
+}
+
+/// # Find Control Characters.
+///
+/// Recursively search the attribute values and text of a node for
+/// non-whitespace control characters — stray nulls and the like — that tend to
+/// trip up downstream parsers, recording their locations in `out`.
+fn control_chars(src: &dyn Node, out: &mut Vec<String>) {
+	/// # Is Stray Control Character?
+	const fn is_stray(c: char) -> bool {
+		c.is_ascii_control() && ! matches!(c, '\t' | '\n' | '\r')
+	}
+
+	let name = src.get_name();
+	if let Some(attrs) = src.get_attributes() {
+		let mut keys: Vec<&String> = attrs.iter()
+			.filter_map(|(k, v)| if v.chars().any(is_stray) { Some(k) } else { None })
+			.collect();
+		keys.sort_unstable();
+		for k in keys {
+			let loc = format!("<{name}> {k}");
+			if ! out.contains(&loc) { out.push(loc); }
+		}
+	}
+
+	for child in src.get_children().map_or(&[][..], Vec::as_slice) {
+		// Text nodes have no attributes.
+		if child.get_attributes().is_none() {
+			if child.to_string().chars().any(is_stray) {
+				let loc = format!("<{name}> text");
+				if ! out.contains(&loc) { out.push(loc); }
+			}
+		}
+		else { control_chars(child.as_ref(), out); }
+	}
+}
+
+/// # Find Unused Namespaces.
+///
+/// Return the (sorted) `xmlns:*` prefixes declared anywhere within the node
+/// that none of its element or attribute names actually use, e.g. editor
+/// cruft like `xmlns:cc` left behind after its metadata was stripped.
+///
+/// Element names are lowercased during parsing, so prefixes are compared
+/// case-insensitively.
+fn unused_namespaces(src: &dyn Node) -> Vec<String> {
+	/// # Collect Prefixes.
+	fn collect(src: &dyn Node, declared: &mut BTreeSet<String>, used: &mut BTreeSet<String>) {
+		if let Some((prefix, _)) = src.get_name().split_once(':') {
+			used.insert(prefix.to_ascii_lowercase());
+		}
+		if let Some(attrs) = src.get_attributes() {
+			for k in attrs.keys() {
+				if let Some((prefix, local)) = k.split_once(':') {
+					if prefix == "xmlns" { declared.insert(local.to_owned()); }
+					else { used.insert(prefix.to_ascii_lowercase()); }
+				}
+			}
+		}
+		for child in src.get_children().map_or(&[][..], Vec::as_slice) {
+			collect(child.as_ref(), declared, used);
+		}
+	}
+
+	let mut declared = BTreeSet::new();
+	let mut used = BTreeSet::new();
+	collect(src, &mut declared, &mut used);
+	declared.into_iter()
+		.filter(|p| ! used.contains(&p.to_ascii_lowercase()))
+		.collect()
+}
+
+/// # Dump Events.
+///
+/// Return a human-readable, indented listing of the parser events for the
+/// (outermost) `<svg>` element in `raw`, for troubleshooting purposes. Tag
+/// names are lowercased, and attributes are sorted, as they would be in the
+/// final map.
+pub(super) fn dump_events(raw: &str) -> String {
+	use std::fmt::Write;
+
+	let Some((start, end)) = ranges(raw.as_bytes()) else {
+		return String::from("!error: no <svg> element found\n");
+	};
+
+	let mut out = String::new();
+	let mut depth: usize = 0;
+	for event in Parser::new(&raw[start..end]) {
+		match event {
+			Event::Tag(name, kind, attrs) => {
+				let name = name.to_ascii_lowercase();
+				if matches!(kind, Type::End) {
+					depth = depth.saturating_sub(1);
+					let _res = writeln!(out, "{:depth$}</{name}>", "", depth=depth * 2);
+					continue;
+				}
+
+				let mut attrs: Vec<(String, Value)> = attrs.into_iter().collect();
+				attrs.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+				let _res = write!(out, "{:depth$}<{name}", "", depth=depth * 2);
+				for (k, v) in attrs {
+					let _res = write!(out, " {k}=\"{v}\"");
+				}
+
+				if matches!(kind, Type::Empty) { out.push_str("/>\n"); }
+				else {
+					out.push_str(">\n");
+					depth += 1;
+				}
+			},
+			Event::Text(s) => {
+				let s = s.trim();
+				if ! s.is_empty() {
+					let _res = writeln!(out, "{:depth$}#text {s:?}", "", depth=depth * 2);
+				}
+			},
+			Event::Comment(_) => {
+				let _res = writeln!(out, "{:depth$}#comment", "", depth=depth * 2);
+			},
+			Event::Declaration(_) | Event::Instruction(_) => {
+				let _res = writeln!(out, "{:depth$}#declaration", "", depth=depth * 2);
+			},
+			Event::Error(e) => {
+				let _res = writeln!(out, "!error: {e}");
+				break;
+			},
+		}
+	}
+
+	out
+}
+
+/// # Has Duplicate Attributes?
+///
+/// Scan the attributes of a raw tag — beginning just after its name and
+/// running until its closing `>` — returning `true` if any key appears more
+/// than once.
+fn has_duplicate_attrs(src: &str) -> bool {
+	let mut seen: Vec<&str> = Vec::new();
+	let mut rest = src;
+	loop {
+		// Find the next key, if any.
+		rest = rest.trim_start();
+		let len = rest.find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+			.unwrap_or(rest.len());
+		if len == 0 { return false; }
+
+		let key = &rest[..len];
+		if seen.contains(&key) { return true; }
+		seen.push(key);
+
+		// Skip past the value, if any.
+		rest = rest[len..].trim_start();
+		if let Some(next) = rest.strip_prefix('=') {
+			let next = next.trim_start();
+			rest =
+				if let Some(q @ ('"' | '\'')) = next.chars().next() {
+					let Some(idx) = next[1..].find(q) else { return false; };
+					&next[idx + 2..]
+				}
+				else {
+					let idx = next.find(|c: char| c.is_whitespace() || c == '>')
+						.unwrap_or(next.len());
+					&next[idx..]
+				};
+		}
+	}
+}
+
+/// # Well-Formed?
+///
+/// Check a raw document against the XML well-formedness rules the (lenient)
+/// parser lets slide — balanced tags, quoted attribute values, valid names,
+/// character and entity references, etc. — returning a description of the
+/// first violation found, if any.
+fn well_formed(src: &str) -> Result<(), String> {
+	well_formed_inner(src).map_err(|(pos, msg)|
+		format!("{msg} (line {})", src[..pos].matches('\n').count() + 1)
+	)
+}
+
+/// # Well-Formed? (Inner).
+///
+/// This does the actual work for [`well_formed`], returning the byte position
+/// alongside any error.
+fn well_formed_inner(src: &str) -> Result<(), (usize, String)> {
+	let bytes = src.as_bytes();
+	let err = |pos: usize, msg: &str| Err((pos, msg.to_owned()));
+
+	// Illegal characters can appear anywhere.
+	if let Some((pos, c)) = src.char_indices().find(|(_, c)|
+		(c.is_control() && ! matches!(c, '\t' | '\n' | '\r')) || matches!(c, '\u{fffe}' | '\u{ffff}')
+	) {
+		return err(pos, &format!("illegal character U+{:04X}", u32::from(c)));
+	}
+
+	let mut entities: BTreeSet<&str> = BTreeSet::new();
+	let mut stack: Vec<&str> = Vec::new();
+	let mut root = false;
+	let mut pos = usize::from(src.starts_with('\u{feff}')) * 3;
+	while pos < bytes.len() {
+		// Text runs up to the next tag.
+		let next = src[pos..].find('<').map_or(bytes.len(), |idx| pos + idx);
+		let text = &src[pos..next];
+		if stack.is_empty() && ! text.trim().is_empty() {
+			return err(pos, "text outside the root element");
+		}
+		xml_refs(text, &entities).map_err(|(idx, msg)| (pos + idx, msg))?;
+		pos = next;
+		if bytes.len() <= pos { break; }
+
+		let rest = &src[pos..];
+		if let Some(body) = rest.strip_prefix("<!--") {
+			let Some(end) = body.find("-->") else { return err(pos, "unterminated comment"); };
+			if body[..end].contains("--") || body[..end].ends_with('-') {
+				return err(pos, "-- within a comment");
+			}
+			pos += 7 + end;
+		}
+		else if let Some(body) = rest.strip_prefix("<![CDATA[") {
+			if stack.is_empty() { return err(pos, "CDATA outside the root element"); }
+			let Some(end) = body.find("]]>") else { return err(pos, "unterminated CDATA section"); };
+			pos += 12 + end;
+		}
+		else if let Some(body) = rest.strip_prefix("<?") {
+			let Some(end) = body.find("?>") else { return err(pos, "unterminated processing instruction"); };
+			if xml_name(bytes, pos + 2).is_none() { return err(pos, "invalid processing instruction"); }
+			pos += 4 + end;
+		}
+		else if rest.starts_with("<!DOCTYPE") {
+			if root || ! stack.is_empty() { return err(pos, "misplaced DOCTYPE"); }
+			let Some(end) = xml_doctype(rest, &mut entities) else { return err(pos, "unterminated DOCTYPE"); };
+			pos += end + 1;
+		}
+		else if rest.starts_with("</") {
+			let Some(len) = xml_name(bytes, pos + 2) else { return err(pos, "invalid closing tag"); };
+			let tag = &src[pos + 2..pos + 2 + len];
+			let after = src[pos + 2 + len..].trim_start();
+			if ! after.starts_with('>') { return err(pos, &format!("malformed </{tag}>")); }
+			match stack.pop() {
+				Some(open) if open == tag => {},
+				Some(open) => return err(pos, &format!("</{tag}> does not match <{open}>")),
+				None => return err(pos, &format!("unexpected </{tag}>")),
+			}
+			if stack.is_empty() { root = true; }
+			pos = bytes.len() - after.len() + 1;
+		}
+		else {
+			if root && stack.is_empty() { return err(pos, "multiple root elements"); }
+			let Some(len) = xml_name(bytes, pos + 1) else { return err(pos, "invalid tag name"); };
+			let tag = &src[pos + 1..pos + 1 + len];
+			let (end, empty) = xml_attrs(src, pos + 1 + len, tag, &entities)?;
+			if ! empty { stack.push(tag); }
+			else if stack.is_empty() { root = true; }
+			pos = end;
+		}
+	}
+
+	if let Some(open) = stack.last() { return err(bytes.len(), &format!("unclosed <{open}>")); }
+	if ! root { return err(0, "no root element"); }
+	Ok(())
+}
+
+/// # XML Attributes.
+///
+/// Check the attributes of a start tag — beginning just after its name —
+/// returning the position just past its closing `>`, and whether or not it
+/// was self-closing.
+fn xml_attrs(src: &str, mut pos: usize, tag: &str, entities: &BTreeSet<&str>)
+-> Result<(usize, bool), (usize, String)> {
+	let bytes = src.as_bytes();
+	let mut seen: Vec<&str> = Vec::new();
+	loop {
+		let trimmed = src[pos..].trim_start();
+		let spaced = trimmed.len() < src.len() - pos;
+		pos = bytes.len() - trimmed.len();
+
+		if trimmed.starts_with('>') { return Ok((pos + 1, false)); }
+		if trimmed.starts_with("/>") { return Ok((pos + 2, true)); }
+		if trimmed.is_empty() { return Err((pos, format!("unterminated <{tag}>"))); }
+		if ! spaced { return Err((pos, format!("missing whitespace in <{tag}>"))); }
+
+		let Some(len) = xml_name(bytes, pos) else {
+			return Err((pos, format!("invalid attribute name in <{tag}>")));
+		};
+		let key = &src[pos..pos + len];
+		if seen.contains(&key) { return Err((pos, format!("duplicate {key} in <{tag}>"))); }
+		seen.push(key);
+
+		let Some(value) = src[pos + len..].trim_start().strip_prefix('=') else {
+			return Err((pos, format!("{key} has no value in <{tag}>")));
+		};
+		let value = value.trim_start();
+		let Some(q @ ('"' | '\'')) = value.chars().next() else {
+			return Err((pos, format!("unquoted {key} in <{tag}>")));
+		};
+		let start = bytes.len() - value.len() + 1;
+		let Some(end) = src[start..].find(q) else {
+			return Err((pos, format!("unterminated {key} in <{tag}>")));
+		};
+		let value = &src[start..start + end];
+		if value.contains('<') { return Err((pos, format!("< within {key} in <{tag}>"))); }
+		xml_refs(value, entities).map_err(|(idx, msg)| (start + idx, msg))?;
+		pos = start + end + 1;
+	}
+}
+
+/// # XML DOCTYPE.
+///
+/// Find the closing `>` of a DOCTYPE declaration — skipping past any internal
+/// subset — noting the (general) entities it declares along the way.
+fn xml_doctype<'a>(src: &'a str, entities: &mut BTreeSet<&'a str>) -> Option<usize> {
+	let mut depth = 0_usize;
+	let mut quote = None;
+	let end = src.bytes().enumerate().skip(9).find_map(|(idx, b)| {
+		if let Some(q) = quote {
+			if q == b { quote = None; }
+			return None;
+		}
+		match b {
+			b'"' | b'\'' => { quote = Some(b); },
+			b'[' => { depth += 1; },
+			b']' => { depth = depth.saturating_sub(1); },
+			b'>' if depth == 0 => return Some(idx),
+			_ => {},
+		}
+		None
+	})?;
+
+	for (idx, _) in src[..end].match_indices("<!ENTITY") {
+		let decl = src[idx + 8..end].trim_start();
+		if ! decl.starts_with('%') {
+			let len = xml_name(decl.as_bytes(), 0).unwrap_or(0);
+			entities.insert(&decl[..len]);
+		}
+	}
+
+	Some(end)
+}
+
+/// # XML Name.
+///
+/// Return the length of the (valid) XML name starting at `pos`, if any.
+fn xml_name(bytes: &[u8], pos: usize) -> Option<usize> {
+	let first = *bytes.get(pos)?;
+	if first.is_ascii_digit() || matches!(first, b'-' | b'.') || ! is_xml_name(first) {
+		return None;
+	}
+	Some(bytes[pos..].iter().take_while(|b| is_xml_name(**b)).count())
+}
+
+/// # XML Name Byte?
+///
+/// Non-ASCII bytes are given the benefit of the doubt.
+const fn is_xml_name(b: u8) -> bool {
+	b.is_ascii_alphanumeric() || matches!(b, b'_' | b':' | b'-' | b'.') || 0x80 <= b
+}
+
+/// # XML References.
+///
+/// Make sure each `&` in a text or attribute value begins a valid character
+/// or entity reference.
+fn xml_refs(src: &str, entities: &BTreeSet<&str>) -> Result<(), (usize, String)> {
+	for (idx, _) in src.match_indices('&') {
+		let rest = &src[idx + 1..];
+		let Some(r) = rest.find(';').map(|end| &rest[..end]).filter(|r|
+			! r.is_empty() && r.bytes().all(|b| is_xml_name(b) || b == b'#')
+		)
+		else { return Err((idx, String::from("unescaped &"))); };
+
+		let valid = r.strip_prefix('#').map_or_else(
+			|| matches!(r, "amp" | "lt" | "gt" | "quot" | "apos") || entities.contains(r),
+			|n| n.strip_prefix('x')
+				.map_or_else(|| n.parse::<u32>().ok(), |h| u32::from_str_radix(h, 16).ok())
+				.and_then(char::from_u32)
+				.is_some_and(|c| ! c.is_control() || matches!(c, '\t' | '\n' | '\r')),
+		);
+		if ! valid { return Err((idx, format!("invalid reference &{r};"))); }
+	}
+	Ok(())
+}
+
+/// # Error Context.
+///
+/// Return a short, whitespace-collapsed excerpt of `src` surrounding byte
+/// position `pos`, for use in parse error messages.
+fn error_context(src: &str, pos: usize) -> String {
+	/// # Bytes Before.
+	const BEFORE: usize = 24;
+
+	/// # Bytes After.
+	const AFTER: usize = 40;
+
+	let mut start = pos.min(src.len()).saturating_sub(BEFORE);
+	while ! src.is_char_boundary(start) { start -= 1; }
+
+	let mut end = pos.saturating_add(AFTER).min(src.len());
+	while ! src.is_char_boundary(end) { end += 1; }
+
+	src[start..end].split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// # First Characters.
+///
+/// Return the first forty or so characters of `src` — skipping leading
+/// whitespace and collapsing inner runs to single spaces — for use in parse
+/// error messages.
+fn first_chars(src: &str) -> String {
+	/// # Maximum Characters.
+	const MAX: usize = 40;
+
+	let mut out = String::with_capacity(MAX + 3);
+	let mut len = 0;
+	for (idx, word) in src.split_whitespace().enumerate() {
+		let sep = if idx == 0 { None } else { Some(' ') };
+		for c in sep.into_iter().chain(word.chars()) {
+			if MAX <= len {
+				out.push('…');
+				return out;
+			}
+			out.push(c);
+			len += 1;
+		}
+	}
+	out
+}
+
+/// # Parse SVG into Symbol.
+///
+/// This parses and somewhat validates an input SVG, returning it as a `Symbol`
+/// suitable for inclusion in the map.
+///
+/// The path is only used for error reporting; the source is passed in
+/// directly.
+///
+/// Alongside the symbol, any content warnings are returned, as well as a
+/// `bool` indicating whether or not the `viewBox` had to be derived from the
+/// `width`/`height`.
+pub(super) fn parse_svg(raw: &str, path: &Path, id: &str, opts: &MapOptions)
+-> Result<(Symbol, ContentWarnings, bool), SvgError> {
+	// Hold it to a higher standard?
+	if opts.check_well_formed {
+		well_formed(raw).map_err(|e| SvgError::Malformed(path.to_path_buf(), e))?;
+	}
+
+	// Find the start and end ranges. If there's no <svg> at all, show what
+	// the file starts with instead. Otherwise it was probably cut short, so
+	// the end is the interesting part.
+	let (start, end) = ranges(raw.as_bytes()).ok_or_else(||
+		if ! raw.as_bytes().windows(4).any(|w| w.eq_ignore_ascii_case(b"<svg")) {
+			SvgError::ParseStart(path.to_path_buf(), first_chars(raw))
+		}
+		else if opts.error_context {
+			SvgError::ParseContext(path.to_path_buf(), error_context(raw, raw.len()))
+		}
+		else { SvgError::Parse(path.to_path_buf()) }
+	)?;
+	let raw = &raw[start..end];
+
+	// Note when we need to give up by, if ever.
+	let deadline = opts.parse_timeout.map(|d| Instant::now() + d);
+
+	// Parse it, keeping track of where we are in case of errors, and how
+	// deeply nested, since the element builders recurse one level per tag.
+	// Like the builders, only closing tags matching the innermost open
+	// element count; strays are ignored.
+	let mut events: Vec<Event> = Vec::new();
+	let mut pos: usize = 0;
+	let mut open: Vec<&str> = Vec::new();
+	let mut dupes = false;
+	for event in Parser::new(raw) {
+		if expired(deadline) { return Err(SvgError::ParseTimeout(path.to_path_buf())); }
+		match event {
+			Event::Error(_) =>
+				return Err(
+					if opts.error_context {
+						SvgError::ParseContext(path.to_path_buf(), error_context(raw, pos))
+					}
+					else { SvgError::Parse(path.to_path_buf()) }
+				),
+			// Tags only give us the name, so we need to look ahead to find
+			// where they actually end.
+			Event::Tag(s, kind, _) => {
+				pos = s.as_ptr().addr() - raw.as_ptr().addr() + s.len();
+
+				match kind {
+					Type::Start => {
+						open.push(s);
+						if opts.max_depth.get() < open.len() {
+							return Err(SvgError::NestingTooDeep(path.to_path_buf(), opts.max_depth));
+						}
+					},
+					Type::End => {
+						if open.last().is_some_and(|o| o.eq_ignore_ascii_case(s)) { open.pop(); }
+					},
+					Type::Empty => {},
+				}
+
+				// While we're here, make sure no attributes were specified
+				// more than once, since the parser will silently keep only
+				// the last.
+				if ! dupes { dupes = has_duplicate_attrs(&raw[pos..]); }
+
+				pos = raw[pos..].find('>').map_or(pos, |idx| pos + idx + 1);
+				events.push(event);
+			},
+			Event::Text(s) => {
+				pos = s.as_ptr().addr() - raw.as_ptr().addr() + s.len();
+				events.push(event);
+			},
+			Event::Comment(s) | Event::Declaration(s) | Event::Instruction(s) => {
+				pos = s.as_ptr().addr() - raw.as_ptr().addr() + s.len();
+			},
+		}
+	}
+
+	log::debug(format_args!("{id}: parsed {} events.", events.len()));
+
+	// The last event should be a closing SVG tag.
+	match events.pop() {
+		Some(Event::Tag(s, Type::End, _)) if s.eq_ignore_ascii_case("svg") => {},
+		_ => return Err(SvgError::Parse(path.to_path_buf())),
+	}
+
+	// Grab the main element.
+	events.reverse();
+	let (out, wrap, derived) = parse_main(events.pop(), path, opts)?;
+	let mut out = out.set("id", id);
+	if derived {
+		log::debug(format_args!("{id}: derived the viewBox from the width and height."));
+	}
+
+	// Check for styles, classes, and IDs that may cause issues.
+	let mut warn = content_warnings(&events);
+	if dupes { warn |= ContentWarnings::DUPLICATE_ATTRS; }
+
+	// Append the children.
+	while ! events.is_empty() {
+		let next = parse_flat(&mut events, opts, deadline, 2).ok_or_else(||
+			if expired(deadline) { SvgError::ParseTimeout(path.to_path_buf()) }
+			else { SvgError::Parse(path.to_path_buf()) }
+		)?;
+		if is_empty(&next, opts) {
+			log::debug(format_args!("{id}: dropped an empty <{}>.", next.get_name()));
+		}
+		else { out.append(next); }
+	}
+
+	// Move the content — aside from the title and description — into the
+	// wrapper carrying the root's presentation attributes, if any.
+	if let Some(mut wrap) = wrap {
+		let (keep, moved): (Vec<_>, Vec<_>) = std::mem::take(Element::get_children_mut(&mut out))
+			.into_iter()
+			.partition(|c| matches!(c.get_name(), "title" | "desc"));
+		if ! moved.is_empty() {
+			log::debug(format_args!("{id}: wrapped the content in a group with the root's presentation attributes."));
+			Element::get_children_mut(&mut wrap).extend(moved);
+			out.append(wrap);
+		}
+		Element::get_children_mut(&mut out).splice(0..0, keep);
+	}
+
+	// Strip version info?
+	if opts.strip_version { strip_attributes(&mut out, &["version", "baseProfile"]); }
+
+	// Tidy up attribute whitespace?
+	if opts.clean_attr_whitespace { clean_attr_whitespace(&mut out); }
+
+	// Tidy the path data?
+	if opts.trim_path_whitespace { trim_path_whitespace(&mut out); }
+
+	// Compact the path data?
+	if opts.minify { minify_paths(&mut out); }
+
+	// Check for font-dependent text.
+	if has_text(&out) { warn |= ContentWarnings::TEXT; }
+
+	// Namespace the inner IDs?
+	if opts.normalize_ids_to_prefix { prefix_inner_ids(&mut out, id); }
+
+	if ! warn.is_empty() { log::debug(format_args!("{id}: content warnings: {warn}.")); }
+
+	Ok((out, warn, derived))
+}
+
+/// # Flatten Next Element.
+///
+/// This returns the next element, recursing as necessary to capture all its
+/// children.
+///
+/// The `depth` is that of the element itself, the root `<svg>` being `1`.
+///
+/// If the `deadline` passes or the depth exceeds the maximum, `None` is
+/// returned.
+fn parse_flat(
+	events: &mut Vec<Event>,
+	opts: &MapOptions,
+	deadline: Option<Instant>,
+	depth: usize,
+) -> Option<Element> {
+	let next = events.pop()?;
+	match next {
+		// It already is flat!
+		Event::Tag(name, Type::Empty, attrs) => {
+			let mut out = Element::new(name.to_ascii_lowercase());
+			for (k, v) in attrs {
+				out.assign(k, v);
+			}
+			Some(out)
+		},
+		Event::Tag(name, Type::Start, attrs) =>
+			parse_flat2(name.to_ascii_lowercase(), attrs, events, opts, deadline, depth),
+		_ => None,
+	}
+}
+
+/// # Flatten Open Tag.
+///
+/// This builds a flat element beginning from its opening tag and ending with
+/// its closing tag.
+///
+/// If the closing tag is missing, the `deadline` passes, or the `depth`
+/// exceeds the maximum, `None` is returned.
+fn parse_flat2(
+	mut name: String,
+	attrs: Attributes,
+	events: &mut Vec<Event>,
+	opts: &MapOptions,
+	deadline: Option<Instant>,
+	depth: usize,
+) -> Option<Element> {
+	if expired(deadline) || opts.max_depth.get() < depth { return None; }
+
+	name.make_ascii_lowercase();
+	let mut out = Element::new(&name);
+	for (k, v) in attrs {
+		out.assign(k, v);
+	}
+
+	let mut closed = false;
+	let mut text = String::new();
+	while let Some(event) = events.pop() {
+		match event {
+			// We found the end!
+			Event::Tag(s, Type::End, _) if s.eq_ignore_ascii_case(&name) => {
+				closed = true;
+				break;
+			},
+			// Text is buffered so that adjacent fragments (e.g. either side of
+			// a comment) wind up in a single node.
+			Event::Text(s) => { text.push_str(s); },
+			// Such tags are only one level deep.
+			Event::Tag(s, Type::Empty, attrs) => {
+				push_text(&mut out, &mut text);
+				let mut tmp = Element::new(s.to_ascii_lowercase());
+				for (k, v) in attrs {
+					tmp.assign(k, v);
+				}
+				if ! is_empty(&tmp, opts) { out.append(tmp); }
+			},
+			// Recurse.
+			Event::Tag(s, Type::Start, attrs) => {
+				push_text(&mut out, &mut text);
+				if let Some(tmp) = parse_flat2(s.to_ascii_lowercase(), attrs, events, opts, deadline, depth + 1) {
+					if ! is_empty(&tmp, opts) { out.append(tmp); }
+				}
+				// Out of time; give up on the whole thing.
+				else if expired(deadline) { return None; }
+			},
+			_ => {},
+		}
+	}
+
+	if closed {
+		push_text(&mut out, &mut text);
+		Some(out)
+	}
+	else { None }
+}
+
+/// # Push Text.
+///
+/// Append any buffered text to the element as a single (trimmed) node,
+/// clearing the buffer.
+fn push_text(out: &mut Element, text: &mut String) {
+	let s = text.trim();
+	if ! s.is_empty() { out.append(svg::node::Text::new(s)); }
+	text.truncate(0);
+}
+
+/// # Parse Main.
+///
+/// This parses the outer SVG element, ensuring it has a `viewBox`. If it
+/// doesn't, one is derived from the `width` and `height`, if possible.
+///
+/// The [`ViewboxSource`] can restrict this to one or the other.
+///
+/// The returned `bool` indicates whether or not the `viewBox` was derived.
+fn parse_main(event: Option<Event>, path: &Path, opts: &MapOptions)
+-> Result<(Symbol, Option<Element>, bool), SvgError> {
+	if let Some(Event::Tag(s, Type::Start, a)) = event {
+		if s.eq_ignore_ascii_case("svg") {
+			let mut out = Symbol::new();
+			let mut derived = false;
+
+			// Do we have a viewbox?
+			let vb = match opts.viewbox_source {
+				ViewboxSource::WidthHeight => None,
+				_ => a.get("viewBox").or_else(|| a.get("viewbox")).or_else(|| a.get("VIEWBOX")),
+			};
+			if let Some(vb) = vb {
+				out = out.set("viewBox", vb.clone());
+			}
+			else if matches!(opts.viewbox_source, ViewboxSource::Viewbox) {
+				return Err(SvgError::Viewbox(path.to_path_buf()));
+			}
+			else {
+				let vb = parse_wh(
+					a.get("width").or_else(|| a.get("WIDTH")),
+					a.get("height").or_else(|| a.get("HEIGHT")),
+				)
+					.ok_or_else(|| SvgError::Viewbox(path.to_path_buf()))?;
+
+				out = out.set("viewBox", vb);
+				derived = true;
+			}
+
+			// Carry over any other requested attributes.
+			for name in &opts.keep_root_attributes {
+				if let Some((k, v)) = a.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)) {
+					out.assign(k.as_str(), v.clone());
+				}
+			}
+
+			// Carry over data attributes too?
+			if opts.keep_data {
+				for (k, v) in &a {
+					if
+						is_data_attr(k) &&
+						! opts.keep_root_attributes.iter().any(|p| k.eq_ignore_ascii_case(p))
+					{
+						out.assign(k.as_str(), v.clone());
+					}
+				}
+			}
+
+			// Collect presentation attributes for a wrapper group?
+			let mut wrap = None;
+			if opts.inherit_root_presentation {
+				let mut g = Element::new("g");
+				for (k, v) in &a {
+					if
+						PRESENTATION_ATTRS.iter().any(|p| k.eq_ignore_ascii_case(p)) &&
+						! opts.keep_root_attributes.iter().any(|p| k.eq_ignore_ascii_case(p))
+					{
+						g.assign(k.as_str(), v.clone());
+					}
+				}
+				if ! g.get_attributes().is_empty() { wrap.replace(g); }
+			}
+
+			return Ok((out, wrap, derived));
+		}
+	}
+
+	Err(SvgError::Parse(path.to_path_buf()))
+}
+
+/// # Is Data Attribute?
+///
+/// Returns `true` if the name is a (lowercase) custom data attribute, i.e.
+/// `data-[a-z0-9-]+`.
+fn is_data_attr(src: &str) -> bool {
+	src.strip_prefix("data-").is_some_and(|rest|
+		! rest.is_empty() &&
+		rest.bytes().all(|b| matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'-'))
+	)
+}
+
+/// # Parse Root Attribute List.
+///
+/// Parse a comma-separated list of root attribute names to carry over to the
+/// symbol, returning `None` if any are malformed or would conflict with the
+/// symbol's own `id` or `viewBox` (e.g. `width`).
+pub(super) fn parse_root_attributes(src: &str) -> Option<Vec<String>> {
+	let mut out = Vec::new();
+	for name in src.split(',').map(str::trim).filter(|n| ! n.is_empty()) {
+		if
+			! name.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':')) ||
+			! name.as_bytes()[0].is_ascii_alphabetic() ||
+			["id", "viewbox", "width", "height", "x", "y"].iter().any(|n| name.eq_ignore_ascii_case(n))
+		{
+			return None;
+		}
+		out.push(name.to_owned());
+	}
+
+	if out.is_empty() { None }
+	else { Some(out) }
+}
+
+/// # Parse ID Comment.
+///
+/// Return the ID declared by a `<!-- yesvgmap-id: … -->` comment ahead of the
+/// root `<svg>` tag, if any. (It is returned as-is; validation is left to the
+/// caller.)
+fn parse_id_comment(raw: &str) -> Option<&str> {
+	let (start, _) = ranges(raw.as_bytes())?;
+	let prolog = &raw[..start];
+	prolog.match_indices("<!--").find_map(|(idx, _)| {
+		let body = &prolog[idx + 4..];
+		let body = &body[..body.find("-->")?];
+		body.trim().strip_prefix("yesvgmap-id:").map(str::trim)
+	})
+}
+
+/// # Parse ID Map.
+///
+/// Parse a tab-separated list of `source-stem<TAB>id` pairs, one per line,
+/// ignoring blank lines. The IDs take the place of the sanitized file stems,
+/// so must consist solely of ASCII alphanumerics, `-`, and `_`.
+///
+/// If a line is malformed, or repeats a stem, its (1-based) number is
+/// returned as the error.
+pub(super) fn parse_id_map(src: &str) -> Result<BTreeMap<String, String>, usize> {
+	let mut out = BTreeMap::new();
+	for (idx, line) in src.lines().enumerate() {
+		if line.trim().is_empty() { continue; }
+
+		let (stem, id) = line.split_once('\t').ok_or(idx + 1)?;
+		let (stem, id) = (stem.trim(), id.trim());
+		if
+			stem.is_empty() ||
+			! valid_stem(id) ||
+			out.insert(stem.to_owned(), id.to_owned()).is_some()
+		{
+			return Err(idx + 1);
+		}
+	}
+	Ok(out)
+}
+
+/// # Parse Usage Data.
+///
+/// Parse a tab-separated list of `id<TAB>count` pairs, one per line, ignoring
+/// blank lines.
+///
+/// If a line is malformed, or repeats an ID, its (1-based) number is returned
+/// as the error.
+pub(super) fn parse_usage_data(src: &str) -> Result<BTreeMap<String, u64>, usize> {
+	let mut out = BTreeMap::new();
+	for (idx, line) in src.lines().enumerate() {
+		if line.trim().is_empty() { continue; }
+
+		let (id, count) = line.split_once('\t').ok_or(idx + 1)?;
+		let id = id.trim();
+		let count = count.trim().parse::<u64>().map_err(|_| idx + 1)?;
+		if id.is_empty() || out.insert(id.to_owned(), count).is_some() {
+			return Err(idx + 1);
+		}
+	}
+	Ok(out)
+}
+
+/// # Valid Stem?
+///
+/// Returns `true` if a user-supplied ID stem is non-empty and consists solely
+/// of ASCII alphanumerics, `-`, and `_`.
+fn valid_stem(src: &str) -> bool {
+	! src.is_empty() &&
+	src.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+}
+
+/// # Parse STDIN ID.
+///
+/// Validate a symbol ID for `--stdin`, returning `None` unless it consists
+/// solely of ASCII alphanumerics, `-`, and `_`, and begins with a letter —
+/// or under `relaxed` rules, a letter, digit, or `_` (see
+/// [`valid_id_start`]).
+pub(super) fn parse_stdin_id(src: &str, relaxed: bool) -> Option<String> {
+	let src = src.trim();
+	if
+		valid_id_start(src, relaxed) &&
+		src.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+	{
+		Some(src.to_owned())
+	}
+	else { None }
+}
+
+/// # Parse Symbol Class.
+///
+/// Validate a class name for `--symbol-class`, returning `None` unless it is
+/// a single CSS identifier-style token: ASCII alphanumerics, dashes, and
+/// underscores, not beginning with a digit (or a dash and a digit).
+pub(super) fn parse_symbol_class(src: &str) -> Option<String> {
+	let src = src.trim();
+	let bytes = src.as_bytes();
+	let first = bytes.iter().position(|b| *b != b'-')?;
+	if
+		1 < first ||
+		bytes[first].is_ascii_digit() ||
+		! bytes.iter().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+	{
+		return None;
+	}
+
+	Some(src.to_owned())
+}
+
+/// # Sprite Symbols.
+///
+/// Read the `<symbol>` elements from a previously generated map, returning
+/// each by ID along with its (compact) serialization, suitable for comparing
+/// one map against another.
+///
+/// Returns `None` if the map cannot be parsed.
+pub(super) fn sprite_symbols(raw: &str) -> Option<BTreeMap<String, String>> {
+	let (start, end) = ranges(raw.as_bytes())?;
+	let mut events: Vec<Event> = Vec::new();
+	for event in Parser::new(&raw[start..end]) {
+		match event {
+			Event::Error(_) => return None,
+			Event::Tag(..) | Event::Text(_) => { events.push(event); },
+			_ => {},
+		}
+	}
+
+	// Skip past the root.
+	events.reverse();
+	if ! matches!(events.pop(), Some(Event::Tag(s, Type::Start, _)) if s.eq_ignore_ascii_case("svg")) {
+		return None;
+	}
+
+	let opts = MapOptions::default();
+	let mut out = BTreeMap::new();
+	while ! events.is_empty() {
+		if let Some(next) = parse_flat(&mut events, &opts, None, 2) {
+			if next.get_name() == "symbol" {
+				if let Some(id) = next.get_attributes().get("id") {
+					let mut src = String::new();
+					render_compact(&next, false, &[], SelfClosing::Always, &mut src);
+					out.insert(id.to_string(), src);
+				}
+			}
+			// Sprites built with --mode defs keep their images in groups.
+			else if next.get_name() == "defs" {
+				for child in next.get_children() {
+					if child.get_name() != "g" { continue; }
+					if let Some(id) = child.get_attributes().and_then(|a| a.get("id")) {
+						let mut src = String::new();
+						render_compact(child.as_ref(), false, &[], SelfClosing::Always, &mut src);
+						out.insert(id.to_string(), src);
+					}
+				}
+			}
+		}
+	}
+
+	Some(out)
+}
+
+/// # Path Stem to ID.
+///
+/// Take the ASCII alphanumeric and `-` characters from the file stem and
+/// return them for use as an ID suffix.
+///
+/// Note that leading and trailing dashes are _not_ trimmed; `-close-.svg`
+/// becomes `-close-` (and ultimately `PREFIX--close-`).
+pub(super) fn parse_stem_id(path: &Path) -> Option<Cow<'_, str>> {
+	let mut out = path.file_stem()?.to_string_lossy();
+
+	// Reduce to alphanumeric and -.
+	if out.chars().any(|c| c != '-' && ! c.is_ascii_alphanumeric()) {
+		out.to_mut().retain(|c: char| c == '-' || c.is_ascii_alphanumeric());
+	}
+
+	// Return it if we got it.
+	if out.is_empty() { None }
+	else { Some(out) }
+}
+
+/// # Render ID Template.
+///
+/// Build a symbol ID from a template, replacing the following placeholders:
+/// * `{prefix}`: the ID prefix;
+/// * `{stem}`: the sanitized file stem;
+/// * `{dir}`: the sanitized name of the file's parent directory;
+/// * `{hash}`: the first `hash_len` hex digits of a hash of the file's contents;
+///
+/// The result is reduced to ASCII alphanumerics, `-`, and `_`, and must begin
+/// with a letter — or under `relaxed` rules, a letter, digit, or `_` (see
+/// [`valid_id_start`]) — otherwise `None` is returned. `None` is also returned if
+/// the template contains any unrecognized placeholders.
+#[expect(clippy::literal_string_with_formatting_args, reason = "These are template placeholders.")]
+fn parse_id_template(
+	tpl: &str,
+	prefix: &str,
+	path: &Path,
+	stem: &str,
+	raw: &str,
+	relaxed: bool,
+	hash_len: usize,
+) -> Option<String> {
+	let dir = path.parent()
+		.and_then(|p| p.file_name())
+		.map(|p| {
+			let mut p = p.to_string_lossy().into_owned();
+			p.retain(|c: char| c == '-' || c.is_ascii_alphanumeric());
+			p
+		})
+		.unwrap_or_default();
+
+	let mut out = tpl.replace("{prefix}", prefix)
+		.replace("{stem}", stem)
+		.replace("{dir}", &dir);
+	if out.contains("{hash}") {
+		let hash = format!("{:016x}", hash64(raw.as_bytes()));
+		out = out.replace("{hash}", &hash[..hash_len.clamp(HASH_LEN_MIN, HASH_LEN_MAX)]);
+	}
+	if out.contains(['{', '}']) { return None; }
+
+	out.retain(|c: char| matches!(c, '-' | '_') || c.is_ascii_alphanumeric());
+	if valid_id_start(&out, relaxed) { Some(out) }
+	else { None }
+}
+
+/// # Unique ID.
+///
+/// Render an ID — via the callback, which receives the desired content hash
+/// length — starting with the shortest hash and lengthening it until the
+/// result is no longer `taken` (or can't get any longer).
+///
+/// Only the hash length varies, so if a collision persists at full length, the
+/// sources are (almost certainly) identical, and the duplicate is left for the
+/// caller to deal with.
+fn unique_id<F, T>(render: F, taken: T) -> Option<String>
+where
+	F: Fn(usize) -> Option<String>,
+	T: Fn(&str) -> bool,
+{
+	let mut len = HASH_LEN_MIN;
+	let mut id = render(len)?;
+	while len < HASH_LEN_MAX && taken(&id) {
+		len += 4;
+		let next = render(len)?;
+
+		// If nothing changed, there's no hash to lengthen.
+		if next == id { break; }
+		id = next;
+	}
+
+	Some(id)
+}
+
+/// # Hash (FNV-1a, 64-bit).
+///
+/// This is used to generate short content hashes for IDs. It isn't
+/// cryptographic, but doesn't need to be.
+const fn hash64(src: &[u8]) -> u64 {
+	let mut out: u64 = 0xcbf2_9ce4_8422_2325;
+	let mut idx = 0;
+	while idx < src.len() {
+		out ^= src[idx] as u64;
+		out = out.wrapping_mul(0x0000_0100_0000_01b3);
+		idx += 1;
+	}
+	out
+}
+
+/// # Reserved Prefix?
+///
+/// Returns `true` if the prefix is (case-insensitively) the name of an SVG
+/// element, e.g. `symbol` or `use`. Such prefixes work, but can be confusing
+/// in selectors built from the resulting IDs.
+pub(super) fn is_reserved_prefix(prefix: &str) -> bool {
+	/// # SVG Element Names.
+	const TAGS: &[&str] = &[
+		"a", "animate", "animatemotion", "animatetransform", "circle",
+		"clippath", "defs", "desc", "ellipse", "feblend", "fecolormatrix",
+		"fecomponenttransfer", "fecomposite", "feconvolvematrix",
+		"fediffuselighting", "fedisplacementmap", "fedistantlight",
+		"fedropshadow", "feflood", "fefunca", "fefuncb", "fefuncg", "fefuncr",
+		"fegaussianblur", "feimage", "femerge", "femergenode", "femorphology",
+		"feoffset", "fepointlight", "fespecularlighting", "fespotlight",
+		"fetile", "feturbulence", "filter", "foreignobject", "g", "image",
+		"line", "lineargradient", "marker", "mask", "metadata", "mpath",
+		"path", "pattern", "polygon", "polyline", "radialgradient", "rect",
+		"script", "set", "stop", "style", "svg", "switch", "symbol", "text",
+		"textpath", "title", "tspan", "use", "view",
+	];
+
+	TAGS.iter().any(|t| t.eq_ignore_ascii_case(prefix))
+}
+
+/// # Output Path to Prefix.
+///
+/// Derive a symbol ID prefix from the output file name, using the same
+/// sanitization rules as [`parse_stem_id`]. A leading `sprite-` is dropped, so
+/// e.g. `sprite-nav.svg` becomes `nav`.
+///
+/// As with IDs, the prefix must begin with a letter unless `relaxed` (see
+/// [`valid_id_start`]).
+pub(super) fn parse_output_prefix(path: &Path, relaxed: bool) -> Option<Cow<'_, str>> {
+	let mut out = parse_stem_id(path)?;
+	if
+		out.len() > 7 &&
+		out.as_bytes()[..7].eq_ignore_ascii_case(b"sprite-")
+	{
+		out.to_mut().drain(..7);
+	}
+
+	// The prefix also needs to make for a valid ID.
+	if valid_id_start(&out, relaxed) { Some(out) }
+	else { None }
+}
+
+/// # Valid ID Start?
+///
+/// Strictly speaking, IDs should begin with an ASCII letter. This keeps them
+/// valid as both XML names and unescaped CSS selectors.
+///
+/// The `relaxed` rules additionally allow a leading digit or `_`, e.g.
+/// `2048-game`. Modern browsers have no trouble resolving such IDs via
+/// `<use href="#…">`, but they are not valid XML `NCName`s, so stricter XML
+/// tooling may reject them, and CSS selectors will need to escape the leading
+/// digit.
+pub(super) fn valid_id_start(id: &str, relaxed: bool) -> bool {
+	id.starts_with(|c: char|
+		c.is_ascii_alphabetic() ||
+		(relaxed && (c == '_' || c.is_ascii_digit()))
+	)
+}
+
+/// # Parse Width/Height.
+///
+/// This attempts to build a `viewBox` value from a `width` and `height`,
+/// returning `None` if either is missing or non-positive.
+fn parse_wh(w: Option<&Value>, h: Option<&Value>) -> Option<String> {
+	let w: &str = w?.trim_matches(|c: char| ! matches!(c, '0'..='9' | '.' | '-'));
+	let h: &str = h?.trim_matches(|c: char| ! matches!(c, '0'..='9' | '.' | '-'));
+
+	// If they're both positive, we're good.
+	if 0.0 < w.parse::<f32>().ok()? && 0.0 < h.parse::<f32>().ok()? {
+		let mut out = String::with_capacity(5 + w.len() + h.len());
+		out.push_str("0 0 ");
+		out.push_str(w);
+		out.push(' ');
+		out.push_str(h);
+		Some(out)
+	}
+	else { None }
+}
+
+/// # Strip Attributes.
+///
+/// Recursively remove the named attributes (case-insensitively) from a node
+/// and all of its descendants.
+fn strip_attributes(src: &mut dyn Node, names: &[&str]) {
+	if let Some(attrs) = src.get_attributes_mut() {
+		attrs.retain(|k, _| ! names.iter().any(|n| k.eq_ignore_ascii_case(n)));
+	}
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			strip_attributes(child.as_mut(), names);
+		}
+	}
+}
+
+/// # Prefix Inner IDs.
+///
+/// Rewrite the IDs of all elements within the symbol as `SYMBOLID__ORIGINAL`
+/// — the original reduced to ASCII alphanumerics, dashes, and underscores —
+/// and update any local references to them. See [`rewrite_ids`] for the
+/// kinds of references supported.
+fn prefix_inner_ids(src: &mut Symbol, id: &str) {
+	let mut ids = Vec::new();
+	for child in Element::get_children(src) { inner_ids(child.as_ref(), &mut ids); }
+	if ids.is_empty() { return; }
+
+	// Work out the replacements, making sure the sanitization doesn't lead to
+	// any collisions.
+	let mut map: Vec<(String, String)> = Vec::with_capacity(ids.len());
+	for old in ids {
+		if map.iter().any(|(o, _)| *o == old) { continue; }
+		let base: String = old.chars()
+			.filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+			.collect();
+		let base = format!("{id}__{base}");
+		let mut new = base.clone();
+		let mut n = 1_usize;
+		while map.iter().any(|(_, v)| *v == new) {
+			n += 1;
+			new = format!("{base}-{n}");
+		}
+		map.push((old, new));
+	}
+
+	// The symbol's own ID stays put, but its other attributes might reference
+	// its children.
+	rewrite_ids(src, &map, true);
+}
+
+/// # Inner IDs.
+///
+/// Recursively collect the `id` attribute values of a node and its children.
+fn inner_ids(src: &dyn Node, out: &mut Vec<String>) {
+	if let Some(id) = src.get_attributes().and_then(|a| a.get("id")) {
+		out.push(id.to_string());
+	}
+	for child in src.get_children().map_or(&[][..], Vec::as_slice) {
+		inner_ids(child.as_ref(), out);
+	}
+}
+
+/// # Rewrite IDs.
+///
+/// Recursively apply the `(old, new)` ID replacements to a node and its
+/// children, covering:
+/// * `id` attributes (except on the `root`);
+/// * `href` and `xlink:href` fragments;
+/// * `url(#…)` references in any attribute, including `style`;
+/// * `aria-labelledby` and `aria-describedby` lists.
+///
+/// References within `<style>` elements are not rewritten.
+fn rewrite_ids(src: &mut dyn Node, map: &[(String, String)], root: bool) {
+	/// # Find Replacement.
+	fn find<'a>(map: &'a [(String, String)], old: &str) -> Option<&'a str> {
+		map.iter().find_map(|(o, n)| (o == old).then_some(n.as_str()))
+	}
+
+	if let Some(attrs) = src.get_attributes_mut() {
+		for (k, v) in attrs.iter_mut() {
+			let new = match k.as_str() {
+				"id" if ! root => find(map, v).map(String::from),
+				"href" | "xlink:href" => v.strip_prefix('#')
+					.and_then(|old| find(map, old))
+					.map(|new| format!("#{new}")),
+				"aria-labelledby" | "aria-describedby" =>
+					if v.split_ascii_whitespace().any(|old| find(map, old).is_some()) {
+						Some(
+							v.split_ascii_whitespace()
+								.map(|old| find(map, old).unwrap_or(old))
+								.collect::<Vec<_>>()
+								.join(" ")
+						)
+					}
+					else { None },
+				_ => rewrite_urls(v, map),
+			};
+			if let Some(new) = new { *v = Value::from(new); }
+		}
+	}
+
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			rewrite_ids(child.as_mut(), map, false);
+		}
+	}
+}
+
+/// # Rewrite `url(#…)` References.
+///
+/// Apply the `(old, new)` ID replacements to any `url(#…)` references in the
+/// value, returning the result if anything changed.
+fn rewrite_urls(src: &str, map: &[(String, String)]) -> Option<String> {
+	let mut out = String::with_capacity(src.len());
+	let mut rest = src;
+	let mut changed = false;
+	while let Some(pos) = rest.find("url(") {
+		// Copy everything up to the fragment.
+		let (before, after) = rest.split_at(pos + 4);
+		let frag = after.trim_start_matches(|c: char|
+			c.is_ascii_whitespace() || c == '"' || c == '\''
+		);
+		out.push_str(before);
+		out.push_str(&after[..after.len() - frag.len()]);
+		rest = frag;
+
+		// Swap the ID, if it's one of ours.
+		if let Some(frag) = frag.strip_prefix('#') {
+			let end = frag.find(|c: char|
+				c == ')' || c == '"' || c == '\'' || c.is_ascii_whitespace()
+			).unwrap_or(frag.len());
+			if let Some((_, new)) = map.iter().find(|(o, _)| *o == frag[..end]) {
+				out.push('#');
+				out.push_str(new);
+				rest = &frag[end..];
+				changed = true;
+			}
+		}
+	}
+
+	if changed {
+		out.push_str(rest);
+		Some(out)
+	}
+	else { None }
+}
+
+/// # Render Compact.
+///
+/// Recursively serialize a node and its children onto `out`, with no
+/// formatting whitespace in between.
+///
+/// If `hidden` is `true`, the node's `hidden` attribute is written without a
+/// value.
+fn render_compact(
+	src: &dyn Node,
+	hidden: bool,
+	order: &[String],
+	self_closing: SelfClosing,
+	out: &mut String,
+) {
+	use std::fmt::Write;
+
+	// Text nodes have no attributes and print themselves.
+	let Some(attrs) = src.get_attributes() else {
+		let _res = write!(out, "{src}");
+		return;
+	};
+
+	// Open the tag.
+	let name = src.get_name();
+	render_open_tag(name, attrs, hidden, order, out);
+
+	// Self-close?
+	let children = src.get_children().map_or(&[][..], Vec::as_slice);
+	if children.is_empty() {
+		self_closing.close_empty(name, out);
+		return;
+	}
+
+	out.push('>');
+	for child in children {
+		render_compact(child.as_ref(), false, order, self_closing, out);
+	}
+	let _res = write!(out, "</{name}>");
+}
+
+/// # Render Opening Tag.
+///
+/// Write the opening `<name` and attributes — sans the closing `>` — onto
+/// `out`. Attributes listed in `order` come first, in that order, followed by
+/// the rest alphabetically.
+///
+/// If `hidden` is `true`, the `hidden` attribute is written without a value.
+fn render_open_tag(
+	name: &str,
+	attrs: &Attributes,
+	hidden: bool,
+	order: &[String],
+	out: &mut String,
+) {
+	use std::fmt::Write;
+
+	out.push('<');
+	out.push_str(name);
+	let mut attrs: Vec<(&String, &Value)> = attrs.iter().collect();
+	attrs.sort_unstable_by(|a, b| {
+		let a_pos = order.iter().position(|o| o == a.0).unwrap_or(usize::MAX);
+		let b_pos = order.iter().position(|o| o == b.0).unwrap_or(usize::MAX);
+		a_pos.cmp(&b_pos).then_with(|| a.0.cmp(b.0))
+	});
+	for (k, v) in attrs {
+		if hidden && k == "hidden" { out.push_str(" hidden"); }
+		else {
+			let v = v.replace('&', "&amp;")
+				.replace('<', "&lt;")
+				.replace('>', "&gt;")
+				.replace('"', "&quot;")
+				.replace('\'', "&apos;");
+			let _res = write!(out, r#" {k}="{v}""#);
+		}
+	}
+}
+
+/// # Render Pretty.
+///
+/// Recursively serialize a node and its children onto `out`, one element per
+/// line, indented by `depth` tabs.
+///
+/// If `hidden` is `true`, the root's `hidden` attribute is written without a
+/// value.
+fn render_pretty(
+	src: &dyn Node,
+	depth: usize,
+	hidden: bool,
+	order: &[String],
+	self_closing: SelfClosing,
+	out: &mut String,
+) {
+	use std::fmt::Write;
+
+	for _ in 0..depth { out.push('\t'); }
+
+	// Text nodes have no attributes and print themselves.
+	let Some(attrs) = src.get_attributes() else {
+		let _res = writeln!(out, "{src}");
+		return;
+	};
+
+	// Open the tag.
+	let name = src.get_name();
+	render_open_tag(name, attrs, hidden && depth == 0, order, out);
+
+	// Self-close?
+	let children = src.get_children().map_or(&[][..], Vec::as_slice);
+	if children.is_empty() {
+		self_closing.close_empty(name, out);
+		out.push('\n');
+		return;
+	}
+
+	// Anything containing text stays inline, lest the indentation wind up
+	// part of the content.
+	if children.iter().any(|c| c.get_attributes().is_none()) {
+		out.push('>');
+		for child in children {
+			render_compact(child.as_ref(), false, order, self_closing, out);
+		}
+		let _res = writeln!(out, "</{name}>");
+		return;
+	}
+
+	out.push_str(">\n");
+	for child in children {
+		render_pretty(child.as_ref(), depth + 1, false, order, self_closing, out);
+	}
+	for _ in 0..depth { out.push('\t'); }
+	let _res = writeln!(out, "</{name}>");
+}
+
+/// # Split Multi-Icon Symbol.
+///
+/// If the (parsed) symbol has two or more top-level `<symbol>` or `<g>`
+/// children with IDs, return each as a separate symbol, along with its
+/// sanitized inner ID — reduced to ASCII alphanumerics and `-`, as with file
+/// stems — and a `bool` indicating whether it inherited the parent's
+/// `viewBox`.
+///
+/// Inner `<symbol>`s contribute their children and keep their own `viewBox`,
+/// if any; `<g>`s are kept whole, minus the ID. Top-level `<defs>` and
+/// `<style>` are shared, i.e. copied into each, while the document-level
+/// `<title>`, `<desc>`, and `<metadata>` are dropped.
+///
+/// Returns `None` if there is nothing to split, or if there is any other
+/// top-level content, since it isn't clear which icon it would belong to.
+fn split_symbol(src: &Symbol) -> Option<Vec<(String, Symbol, bool)>> {
+	/// # Icon ID.
+	fn icon_id(node: &dyn Node) -> Option<String> {
+		if ! matches!(node.get_name(), "g" | "symbol") { return None; }
+		let mut id = node.get_attributes()?.get("id")?.to_string();
+		id.retain(|c: char| c == '-' || c.is_ascii_alphanumeric());
+		if id.is_empty() { None }
+		else { Some(id) }
+	}
+
+	let children = Element::get_children(src);
+	if children.iter().filter_map(|c| icon_id(c.as_ref())).count() < 2 { return None; }
+
+	let viewbox = Element::get_attributes(src).get("viewBox")?;
+	let mut shared: Vec<&Box<dyn Node>> = Vec::new();
+	for c in children {
+		if icon_id(c.as_ref()).is_some() { continue; }
+		match c.get_name() {
+			"defs" | "style" => { shared.push(c); },
+			"desc" | "metadata" | "title" => {},
+			_ => return None,
+		}
+	}
+
+	let mut out = Vec::new();
+	for child in children {
+		let Some(id) = icon_id(child.as_ref()) else { continue; };
+		let own = child.get_attributes().and_then(|a| a.get("viewBox"))
+			.filter(|_| child.get_name() == "symbol");
+		let mut s = Symbol::new().set("viewBox", own.unwrap_or(viewbox).clone());
+		for c in &shared { s.append((*c).clone()); }
+
+		if child.get_name() == "symbol" {
+			for c in child.get_children().into_iter().flatten() { s.append(c.clone()); }
+		}
+		else {
+			let mut g = child.clone();
+			if let Some(attrs) = g.get_attributes_mut() { attrs.remove("id"); }
+			s.append(g);
+		}
+
+		out.push((id, s, own.is_none()));
+	}
+
+	Some(out)
+}
+
+/// # Fixed Dimensions.
+///
+/// Return the root's `width` and `height` attributes — e.g. `width="24px"` —
+/// if either is given in absolute units (`px`, `pt`, `mm`, etc.) alongside a
+/// `viewBox`. Such images render at that fixed size when used directly, even
+/// though the dimensions aren't carried over to the symbol.
+///
+/// Unitless and relative values (`%`, `em`, …) are ignored.
+fn fixed_dimensions(raw: &str) -> Option<String> {
+	/// # Absolute Units.
+	const UNITS: [&str; 7] = ["px", "pt", "pc", "mm", "cm", "in", "q"];
+
+	/// # Absolute Length?
+	fn absolute(src: &str) -> bool {
+		let src = src.trim().to_ascii_lowercase();
+		UNITS.iter().any(|u| src.strip_suffix(u).is_some_and(|n| n.trim().parse::<f64>().is_ok()))
+	}
+
+	let (start, end) = ranges(raw.as_bytes())?;
+	let attrs = Parser::new(&raw[start..end]).find_map(|e|
+		if let Event::Tag(s, Type::Start | Type::Empty, a) = e { s.eq_ignore_ascii_case("svg").then_some(a) }
+		else { None }
+	)?;
+	if ! attrs.keys().any(|k| k.eq_ignore_ascii_case("viewBox")) { return None; }
+
+	let mut out: Vec<(&String, &Value)> = attrs.iter()
+		.filter(|(k, _)| k.eq_ignore_ascii_case("width") || k.eq_ignore_ascii_case("height"))
+		.collect();
+	if ! out.iter().any(|(_, v)| absolute(v)) { return None; }
+
+	// Width before height.
+	out.sort_by_key(|(k, _)| ! k.eq_ignore_ascii_case("width"));
+	Some(
+		out.into_iter()
+			.map(|(k, v)| format!("{k}={:?}", v.trim()))
+			.collect::<Vec<_>>()
+			.join(" ")
+	)
+}
+
+/// # Strict Issues.
+///
+/// Return the `(id, issue)` pairs for any symbols with control characters,
+/// zero-dimension shapes, or rounded `viewBox`es. (These are only populated
+/// when the corresponding checks are enabled.)
+fn strict_issues(entries: &[(Symbol, MapSymbol)]) -> Vec<(String, String)> {
+	let mut out = Vec::new();
+	for (_, s) in entries {
+		if ! s.control_chars.is_empty() {
+			out.push((s.id.clone(), format!("control characters: {}", s.control_chars.join(", "))));
+		}
+		if ! s.zero_shapes.is_empty() {
+			out.push((s.id.clone(), format!("zero-dimension shapes: {}", s.zero_shapes.join(", "))));
+		}
+		if let Some(vb) = s.rounded_viewbox.as_deref() {
+			out.push((s.id.clone(), format!("fractional viewBox: {vb}")));
+		}
+	}
+	out
+}
+
+/// # Symbol Title.
+///
+/// Return the (trimmed) text of the symbol's top-level `<title>`, if any.
+fn symbol_title(src: &Symbol) -> Option<String> {
+	let title = Element::get_children(src).iter()
+		.find(|c| c.get_name() == "title" && c.get_attributes().is_some())?;
+	let text: String = title.get_children()?.iter()
+		.filter(|c| c.get_attributes().is_none())
+		.map(ToString::to_string)
+		.collect();
+	let text = text.trim();
+	if text.is_empty() { None }
+	else { Some(text.to_owned()) }
+}
+
+/// # Title Matches Stem?
+///
+/// Compare a title against a file stem, ignoring case and anything other than
+/// ASCII alphanumerics. They are considered a match if either contains the
+/// other, so e.g. "Close Icon" matches `close.svg`, but "Menu" does not.
+fn title_matches(title: &str, stem: &str) -> bool {
+	/// # Normalize.
+	fn normalize(src: &str) -> String {
+		src.chars()
+			.filter(char::is_ascii_alphanumeric)
+			.map(|c| c.to_ascii_lowercase())
+			.collect()
+	}
+
+	let title = normalize(title);
+	let stem = normalize(stem);
+	title.is_empty() || stem.is_empty() || title.contains(&stem) || stem.contains(&title)
+}
+
+/// # Sort By Modification Time.
+///
+/// Reorder the (alphabetized) entries by source modification time, oldest
+/// first, leaving ties in their original order.
+///
+/// If the times are all identical — as can happen on some filesystems and
+/// containers — the sort would be meaningless, so the entries are left as-is
+/// and `false` is returned.
+fn sort_mtime(entries: &mut [(Symbol, MapSymbol)]) -> bool {
+	let Some(first) = entries.first().map(|(_, s)| s.mtime) else { return true; };
+	if 1 < entries.len() && entries.iter().all(|(_, s)| s.mtime == first) {
+		return false;
+	}
+
+	entries.sort_by_key(|(_, s)| s.mtime);
+	true
+}
+
+/// # Monochrome Colors.
+///
+/// Recursively replace concrete `fill` and `stroke` colors — attributes and
+/// `style` declarations alike — with `currentColor`, so the symbol can be
+/// recolored via CSS.
+///
+/// Non-colors like `none` and `url(#…)` paint server references are left
+/// alone, as is anything within a `<mask>`, where color determines visibility.
+fn mono_colors(src: &mut dyn Node) {
+	/// # Affected Properties.
+	const PROPS: [&str; 2] = ["fill", "stroke"];
+
+	if src.get_name().eq_ignore_ascii_case("mask") { return; }
+
+	if let Some(attrs) = src.get_attributes_mut() {
+		// Attributes first.
+		let keys: Vec<String> = attrs.iter()
+			.filter(|(k, v)|
+				PROPS.iter().any(|p| k.eq_ignore_ascii_case(p)) &&
+				normalize_color(v).is_some()
+			)
+			.map(|(k, _)| k.clone())
+			.collect();
+		for k in keys { attrs.insert(k, Value::from("currentColor")); }
+
+		// Then the styles.
+		let style = attrs.get("style").and_then(|style| {
+			let mut changed = false;
+			let decls: Vec<String> = style.split(';')
+				.map(str::trim)
+				.filter(|d| ! d.is_empty())
+				.map(|decl| match decl.split_once(':') {
+					Some((prop, value)) if
+						PROPS.iter().any(|p| prop.trim().eq_ignore_ascii_case(p)) &&
+						normalize_color(value).is_some()
+					=> {
+						changed = true;
+						format!("{}:currentColor", prop.trim())
+					},
+					_ => decl.to_owned(),
+				})
+				.collect();
+			if changed { Some(decls.join(";")) }
+			else { None }
+		});
+		if let Some(style) = style { attrs.insert("style".to_owned(), Value::from(style)); }
+	}
+
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			mono_colors(child.as_mut());
+		}
+	}
+}
+
+/// # Themable.
+///
+/// If the symbol has more than one distinct color, swap them for custom
+/// properties (see [`theme_colors`]), and add a comment listing them.
+fn themable(src: &mut Symbol, id: &str) {
+	let mut tmp = Vec::new();
+	colors(src, &mut tmp);
+	tmp.sort_unstable();
+	tmp.dedup();
+	if tmp.len() < 2 { return; }
+
+	let mut vars = Vec::new();
+	theme_colors(src, &mut vars);
+	log::debug(format_args!("{id}: swapped {} colors for custom properties.", vars.len()));
+	let list = vars.iter()
+		.enumerate()
+		.map(|(idx, c)| format!("icon-color-{} ({c})", idx + 1))
+		.collect::<Vec<_>>()
+		.join(", ");
+	Element::get_children_mut(src).insert(
+		0,
+		Box::new(Comment::new(format!("Themable colors: {list}."))),
+	);
+}
+
+/// # Theme Colors.
+///
+/// Recursively replace color-valued attributes and `style` declarations with
+/// custom properties — `var(--icon-color-N, ORIGINAL)` — numbered by first
+/// appearance. (Attributes are visited alphabetically for consistency.)
+///
+/// Since presentation attributes can't reliably hold `var()` values, those
+/// are moved into the `style` attribute, ahead of any existing declarations
+/// (which take priority anyway).
+///
+/// The (normalized) colors are recorded in `vars`, such that the index plus
+/// one gives the `N`.
+fn theme_colors(src: &mut dyn Node, vars: &mut Vec<String>) {
+	/// # Swap Color.
+	fn swap(value: &str, vars: &mut Vec<String>) -> Option<String> {
+		let color = normalize_color(value)?;
+		let idx = vars.iter().position(|v| v == &color).unwrap_or_else(|| {
+			vars.push(color);
+			vars.len() - 1
+		});
+		Some(format!("var(--icon-color-{}, {})", idx + 1, value.trim()))
+	}
+
+	if let Some(attrs) = src.get_attributes_mut() {
+		let mut decls: Vec<String> = Vec::new();
+
+		// Attributes first.
+		let mut keys: Vec<String> = attrs.keys()
+			.filter(|k| COLOR_PROPS.iter().any(|p| k.eq_ignore_ascii_case(p)))
+			.cloned()
+			.collect();
+		keys.sort_unstable();
+		for k in keys {
+			if let Some(var) = attrs.get(&k).and_then(|v| swap(v, vars)) {
+				attrs.remove(&k);
+				decls.push(format!("{k}:{var}"));
+			}
+		}
+
+		// Then the styles.
+		let mut changed = ! decls.is_empty();
+		if let Some(style) = attrs.get("style") {
+			for decl in style.split(';').map(str::trim).filter(|d| ! d.is_empty()) {
+				let var = decl.split_once(':').and_then(|(prop, value)|
+					if COLOR_PROPS.iter().any(|p| prop.trim().eq_ignore_ascii_case(p)) {
+						swap(value, vars).map(|var| format!("{}:{var}", prop.trim()))
+					}
+					else { None }
+				);
+				if let Some(var) = var {
+					changed = true;
+					decls.push(var);
+				}
+				else { decls.push(decl.to_owned()); }
+			}
+		}
+
+		if changed { attrs.insert("style".to_owned(), Value::from(decls.join(";"))); }
+	}
+
+	if let Some(children) = src.get_children_mut() {
+		for child in children {
+			theme_colors(child.as_mut(), vars);
+		}
+	}
+}
+
+/// # Find Zero-Dimension Shapes.
+///
+/// Recursively search a node for shapes with explicitly zero-valued
+/// dimensions — e.g. `<rect width="0">` or `<circle r="0">` — which render
+/// nothing, recording their locations in `out`.
+fn zero_shapes(src: &dyn Node, out: &mut Vec<String>) {
+	let name = src.get_name();
+	let keys: &[&str] = match name {
+		"circle" => &["r"],
+		"ellipse" => &["rx", "ry"],
+		"image" | "rect" => &["width", "height"],
+		_ => &[],
+	};
+
+	if let Some(attrs) = src.get_attributes() {
+		for k in keys {
+			let zero = attrs.get(*k).is_some_and(|v|
+				v.trim()
+					.trim_end_matches(|c: char| c == '%' || c.is_ascii_alphabetic())
+					.parse::<f64>()
+					.is_ok_and(|n| n == 0.0)
+			);
+			if zero {
+				let loc = format!("<{name}> {k}");
+				if ! out.contains(&loc) { out.push(loc); }
+			}
+		}
+	}
+
+	for child in src.get_children().map_or(&[][..], Vec::as_slice) {
+		zero_shapes(child.as_ref(), out);
+	}
+}
+
+/// # Normalize Color.
+///
+/// Lowercase the value, strip its whitespace, and expand shorthand hex
+/// (`#abc` to `#aabbcc`) so equivalent colors compare equal.
+///
+/// Custom properties with fallbacks, e.g. `var(--a, red)`, resolve to the
+/// fallback. Values that aren't actual colors — `none`, `currentColor`,
+/// `inherit`, paint server references, etc. — return `None`.
+fn normalize_color(src: &str) -> Option<String> {
+	let mut out: String = src.chars()
+		.filter(|c| ! c.is_ascii_whitespace())
+		.collect();
+	out.make_ascii_lowercase();
+	out = out.trim_end_matches("!important").to_owned();
+
+	// Use the fallback, if any.
+	if out.starts_with("var(") && out.ends_with(')') {
+		let (_, fallback) = out[..out.len() - 1].split_once(',')?;
+		return normalize_color(fallback);
+	}
+
+	if
+		out.is_empty() ||
+		out.starts_with("url(") ||
+		out.starts_with("var(") ||
+		out.starts_with("context-") ||
+		matches!(out.as_str(), "currentcolor" | "inherit" | "initial" | "none" | "unset")
+	{
+		return None;
+	}
+
+	// Expand #rgb and #rgba.
+	if
+		out.starts_with('#') &&
+		matches!(out.len(), 4 | 5) &&
+		out.bytes().skip(1).all(|b| b.is_ascii_hexdigit())
+	{
+		out = out.chars().skip(1).fold(String::from("#"), |mut acc, c| {
+			acc.push(c);
+			acc.push(c);
+			acc
+		});
+	}
+
+	Some(out)
+}
+
+/// # Find Range.
+///
+/// This returns the start byte for the first opening SVG tag and the end byte
+/// of the last closing SVG tag.
+///
+/// Comments, processing instructions (e.g. `<?xml …?>`), and declarations —
+/// including `DOCTYPE`s with internal subsets spanning multiple lines, and
+/// `CDATA` sections — are skipped over, so markup appearing within them is
+/// ignored.
+///
+/// There are a few gotchas to be aware of:
+/// * The opening tag name must be followed by whitespace or `>`;
+/// * Closing tags cannot have any whitespace (they must be exactly `</svg>`);
+/// * Opening tag attributes cannot include unescaped `>` characters;
+/// * Opening tags must precede closing tags;
+/// * There must be an equal number of opening and closing tags;
+///
+/// If any of the above fail, or an open or close cannot be found, `None` is
+/// returned.
+fn ranges(src: &[u8]) -> Option<(usize, usize)> {
+	/// # Opening Marker.
+	const OPEN: &[u8] = b"<svg";
+
+	/// # Closing Marker.
+	const CLOSE: &[u8] = b"</svg>";
+
+	/// # Find Needle.
+	fn find(src: &[u8], needle: &[u8]) -> Option<usize> {
+		src.windows(needle.len()).position(|w| w == needle)
+	}
+
+	/// # Declaration Length.
+	///
+	/// Return the length of the `<!…>` declaration at the start of `src`,
+	/// allowing for (nested) bracketed sections.
+	fn declaration(src: &[u8]) -> Option<usize> {
+		if src.starts_with(b"<![CDATA[") { return find(src, b"]]>").map(|p| p + 3); }
+
+		let mut depth: usize = 0;
+		for (idx, b) in src.iter().copied().enumerate() {
+			match b {
+				b'[' => { depth += 1; },
+				b']' => { depth = depth.checked_sub(1)?; },
+				b'>' if depth == 0 => return Some(idx + 1),
+				_ => {},
+			}
+		}
+		None
+	}
+
+	let mut opens: u8 = 0;
+	let mut closes: u8 = 0;
+
+	let mut start_a: usize = 0;
+	let mut end_a: usize = 0;
+
+	let mut idx: usize = 0;
+	while let Some(pos) = src[idx..].iter().position(|&b| b == b'<') {
+		idx += pos;
+		let rest = &src[idx..];
+
+		// Skip comments, instructions, and declarations.
+		if rest.starts_with(b"<!--") { idx += find(rest, b"-->")? + 3; }
+		else if rest.starts_with(b"<?") { idx += find(rest, b"?>")? + 2; }
+		else if rest.starts_with(b"<!") { idx += declaration(rest)?; }
+		// It's an end!
+		else if rest.len() >= CLOSE.len() && rest[..CLOSE.len()].eq_ignore_ascii_case(CLOSE) {
+			// Can't close until we've opened!
+			if closes == opens { return None; }
+			closes += 1;
+			idx += CLOSE.len();
+			end_a = idx;
+		}
+		// It's a beginning!
+		else if
+			rest.len() > OPEN.len() &&
+			rest[..OPEN.len()].eq_ignore_ascii_case(OPEN) &&
+			(rest[OPEN.len()].is_ascii_whitespace() || rest[OPEN.len()] == b'>')
+		{
+			if opens == 0 {
+				start_a = idx;
+			}
+			opens += 1;
+			idx += OPEN.len();
+		}
+		else { idx += 1; }
+	}
+
+	// We have to have the same number of opens and closes.
+	if 0 < opens && opens == closes {
+		Some((start_a, end_a))
+	}
+	else { None }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// # Temporary SVG.
+	///
+	/// Write `raw` to a temporary file for tests that need real paths.
+	fn tmp_svg(name: &str, raw: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join("yesvgmap-tests");
+		std::fs::create_dir_all(&dir).expect("Unable to create temporary directory.");
+		let path = dir.join(name);
+		std::fs::write(&path, raw).expect("Unable to write temporary SVG.");
+		path
+	}
+
+	#[test]
+	fn test_attribute_order() {
+		let sources = [
+			("i-dot", r#"<svg viewBox="0 0 10 10"><circle r="4" cy="5" fill="red" cx="5"/></svg>"#),
+		];
+		let opts = MapOptions {
+			attribute_order: vec!["xmlns".to_owned(), "id".to_owned(), "fill".to_owned(), "r".to_owned()],
+			id: Some("sprite".to_owned()),
+			..MapOptions::default()
+		};
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg xmlns="http://www.w3.org/2000/svg" id="sprite" aria-hidden="true">"#,
+				r#"<symbol id="i-dot" viewBox="0 0 10 10">"#,
+				r#"<circle fill="red" r="4" cx="5" cy="5"/>"#,
+				"</symbol></svg>",
+			),
+		);
+
+		// The pretty output should follow suit.
+		assert_eq!(
+			map.render(OutputStyle::Pretty),
+			concat!(
+				r#"<svg xmlns="http://www.w3.org/2000/svg" id="sprite" aria-hidden="true">"#, "\n",
+				"\t", r#"<symbol id="i-dot" viewBox="0 0 10 10">"#, "\n",
+				"\t\t", r#"<circle fill="red" r="4" cx="5" cy="5"/>"#, "\n",
+				"\t</symbol>\n",
+				"</svg>",
+			),
+		);
+
+		// Without an order, everything is alphabetical.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-dot" viewBox="0 0 10 10">"#,
+				r#"<circle cx="5" cy="5" fill="red" r="4"/>"#,
+				"</symbol></svg>",
+			),
+		);
+	}
+
+	#[test]
+	fn test_attribute_quotes() {
+		let sources = [
+			("i-quote", r#"<svg viewBox="0 0 10 10"><text font-family='"Comic Sans", Papyrus' title="Don't">Hi</text></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		let expected = r#"<text font-family="&quot;Comic Sans&quot;, Papyrus" title="Don&apos;t">Hi</text>"#;
+		assert!(map.to_string().contains(expected));
+		assert!(map.render(OutputStyle::Pretty).contains(expected));
+	}
+
+	#[test]
+	fn test_clean_attr_whitespace() {
+		let raw = "<svg viewBox=\"0 0 10 10\"><text style=\"fill: red;\n\t\tfont-family: 'Comic  Sans';\n\">Hi</text><path d=\"M0 0\n\th10\n\tv10H0z\"/></svg>";
+
+		// Off.
+		let opts = MapOptions::default();
+		let (s, _, _) = parse_svg(raw, Path::new("a.svg"), "i-a", &opts).expect("Parse failed.");
+		assert!(s.to_string().contains("M0 0\n\th10\n\tv10H0z"));
+
+		// On.
+		let opts = MapOptions { clean_attr_whitespace: true, ..MapOptions::default() };
+		let (s, _, _) = parse_svg(raw, Path::new("a.svg"), "i-a", &opts).expect("Parse failed.");
+		let s = s.to_string().replace('\n', "");
+		assert!(s.contains(r#"<path d="M0 0 h10 v10H0z"/>"#));
+		assert!(s.contains(r#"<text style="fill: red; font-family: &apos;Comic  Sans&apos;;">"#));
+
+		// Direct.
+		assert_eq!(collapse_whitespace("a  b\n\tc "), Some("a b c".to_owned()));
+		assert_eq!(collapse_whitespace(r#"x: "a   b"  y"#), Some(r#"x: "a   b" y"#.to_owned()));
+		assert_eq!(collapse_whitespace("a b c"), None);
+	}
+
+	#[test]
+	fn test_trim_path_whitespace() {
+		for (raw, expected) in [
+			("M 0 , 0  L 10  10", Some("M 0,0 L 10 10")),
+			("\n\tM0,0\r\nL 10 ,10 Z ", Some("M0,0 L 10,10 Z")),
+			(" 0 ,1  2,\t3 ", Some("0,1 2,3")),
+			("M0 0L10 10", None),
+			("M0,0 L-1.5e2 .5", None),
+		] {
+			assert_eq!(trim_path(raw).as_deref(), expected, "Wrong answer for {raw:?}.");
+
+			// The commands and numbers should all survive unchanged.
+			if let Some(trimmed) = expected {
+				assert_eq!(path_tokens(raw, true), path_tokens(trimmed, true), "Geometry changed for {raw:?}.");
+			}
+		}
+
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M 0 , 0  L 10  10" class=" a  b "/><polygon points="0 , 0  5 , 5"/></svg>"#;
+		let (s, _, _) = parse_svg(raw, Path::new("spacey.svg"), "i-spacey", &MapOptions::default())
+			.expect("Parse failed.");
+		assert!(s.to_string().contains(r#"d="M 0 , 0  L 10  10""#));
+
+		let opts = MapOptions { trim_path_whitespace: true, ..MapOptions::default() };
+		let (s, _, _) = parse_svg(raw, Path::new("spacey.svg"), "i-spacey", &opts)
+			.expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.contains(r#"d="M 0,0 L 10 10""#), "Untrimmed: {s}");
+		assert!(s.contains(r#"points="0,0 5,5""#), "Untrimmed: {s}");
+		assert!(s.contains(r#"class=" a  b ""#), "Other attributes should be left alone: {s}");
+	}
+
+	#[test]
+	fn test_minify() {
+		for (raw, expected) in [
+			("M 0.5, 10 L -0.25 3 Z", "M.5 10L-.25 3Z"),
+			("M10 10 h 20 v 20 h -20 z m 5 5", "M10 10h20v20h-20zm5 5"),
+			("M0.5 0.5 L 0.25 0.75", "M.5.5L.25.75"),
+			("M1e-3 2E+2 L 3.5e2 .5", "M1e-3 2E+2L3.5e2 .5"),
+			("M0 0 a1 1 0 011 1 A 10.5, 10.5 0 1 0 -0.5 0.5", "M0 0a1 1 0 0 1 1 1A10.5 10.5 0 1 0-.5.5"),
+			("M-0 +0.5 L 00.5 0.", "M-0+.5L00.5 0."),
+		] {
+			let min = minify_path(raw, true).expect("Minify failed.");
+			assert_eq!(min, expected, "Minify mismatch for {raw:?}.");
+
+			// The commands and numbers should all survive unchanged.
+			let a = path_tokens(raw, true).expect("Tokenize failed.");
+			let b = path_tokens(&min, true).expect("Tokenize failed.");
+			assert_eq!(a.len(), b.len(), "Token count changed for {raw:?}.");
+			for (a, b) in a.into_iter().zip(b) {
+				match (a.parse::<f64>(), b.parse::<f64>()) {
+					(Ok(a), Ok(b)) => assert_eq!(a.to_bits(), b.to_bits(), "Value changed for {raw:?}."),
+					_ => assert_eq!(a, b, "Command changed for {raw:?}."),
+				}
+			}
+
+			// And doing it again shouldn't change anything.
+			assert_eq!(minify_path(&min, true).as_deref(), Some(expected), "Not idempotent: {raw:?}.");
+		}
+
+		// Points.
+		assert_eq!(minify_path("0.5,1 -2 , 3", false).as_deref(), Some(".5 1-2 3"));
+		assert_eq!(minify_path("M0 0", false), None);
+
+		// Garbage.
+		for bad in ["0 0 L1 1", "M0 0 X1 1", "M0 0 L1 #", "M0 0 A1 1 0 2 1 1 1", "M0 0Z1"] {
+			assert_eq!(minify_path(bad, true), None, "Accepted {bad:?}.");
+		}
+
+		// In context.
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M 0.5 0.5 L 9.5 0.5"/><polygon points="0.5, 1 2, 3"/><path d="M0 0 ?"/></svg>"#;
+		let opts = MapOptions { minify: true, ..MapOptions::default() };
+		let (s, _, _) = parse_svg(raw, Path::new("a.svg"), "i-a", &opts).expect("Parse failed.");
+		let s = s.to_string().replace('\n', "");
+		assert!(s.contains(r#"<path d="M.5.5L9.5.5"/>"#), "Unexpected output: {s}");
+		assert!(s.contains(r#"<polygon points=".5 1 2 3"/>"#), "Unexpected output: {s}");
+		assert!(s.contains(r#"<path d="M0 0 ?"/>"#), "Unexpected output: {s}");
+	}
+
+	#[test]
+	fn test_coalesce_text() {
+		let raw = r#"<svg viewBox="0 0 10 10"><text x="1">A<!-- split -->B<tspan>C</tspan> D<!-- split -->E </text></svg>"#;
+		let (s, _, _) = parse_svg(raw, Path::new("text.svg"), "i-text", &MapOptions::default())
+			.expect("Parse failed.");
+
+		let text = Element::get_children(&s).first().expect("Missing text element.");
+		assert_eq!(text.get_name(), "text");
+		let children = text.get_children().expect("Missing text children.");
+		assert_eq!(children.len(), 3, "Text was not coalesced: {children:?}");
+		assert_eq!(children[0].to_string(), "AB");
+		assert_eq!(children[2].to_string(), "DE");
+	}
+
+	#[test]
+	fn test_context_fill() {
+		let raw = r#"<svg viewBox="0 0 10 10"><path fill="context-fill" stroke="context-stroke" d="M0 0h10v10H0z"/><path fill="red" d="M0 0h1"/><path fill="blue" d="M0 0v1"/></svg>"#;
+		let (s, _, _) = parse_svg(raw, Path::new("ctx.svg"), "i-ctx", &MapOptions::default())
+			.expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.contains(r#"fill="context-fill""#), "Missing context-fill: {s}");
+		assert!(s.contains(r#"stroke="context-stroke""#), "Missing context-stroke: {s}");
+
+		// The color passes should leave them be too, even as they rewrite the
+		// concrete colors.
+		for opts in [
+			MapOptions { mono: true, ..MapOptions::default() },
+			MapOptions { themable: true, ..MapOptions::default() },
+		] {
+			let s = Map::with_sources(&opts, &[], &[("i-ctx", raw)])
+				.expect("Map failed.")
+				.to_string();
+			assert!(s.contains(r#"fill="context-fill""#), "Missing context-fill: {s}");
+			assert!(s.contains(r#"stroke="context-stroke""#), "Missing context-stroke: {s}");
+			assert!(! s.contains(r#"fill="red""#), "Color pass didn't run: {s}");
+		}
+	}
+
+	#[test]
+	fn test_control_chars() {
+		let sources = [
+			("i-clean", r#"<svg viewBox="0 0 10 10"><path fill="red" d="M0 0h10v10H0z"/></svg>"#),
+			("i-dirty", "<svg viewBox=\"0 0 10 10\"><path fill=\"red\u{0}\" d=\"M0 0h10v10H0z\"/><text x=\"1\">Hi\u{1b}</text></svg>"),
+		];
+
+		// Nothing is recorded by default.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources)
+			.expect("Map failed.");
+		assert!(map.symbols().iter().all(|s| s.control_chars().is_empty()), "Unexpected control chars.");
+
+		let opts = MapOptions { report_control_chars: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert!(map.symbols()[0].control_chars().is_empty(), "Unexpected control chars.");
+		assert_eq!(map.symbols()[1].control_chars(), ["<path> fill", "<text> text"]);
+	}
+
+	#[test]
+	fn test_unused_namespaces() {
+		let sources = [
+			("i-ns", r#"<svg xmlns:dc="http://purl.org/dc/elements/1.1/" viewBox="0 0 10 10"><g xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:cc="http://creativecommons.org/ns#" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" inkscape:label="Layer"><path d="M0 0h10v10H0z"/></g></svg>"#),
+			("i-used", r##"<svg viewBox="0 0 10 10"><g xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="#a"/></g></svg>"##),
+		];
+
+		// Nothing is recorded by default.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources)
+			.expect("Map failed.");
+		assert!(map.symbols().iter().all(|s| s.unused_namespaces().is_empty()), "Unexpected namespaces.");
+
+		let opts = MapOptions { report_unused_namespaces: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].unused_namespaces(), ["cc", "dc"]);
+		assert!(map.symbols()[1].unused_namespaces().is_empty(), "Unexpected namespaces.");
+	}
+
+	#[test]
+	fn test_dump_events() {
+		let dump = dump_events(include_str!("../test-assets/close.svg"));
+		assert!(
+			dump.starts_with(r#"<svg height="512" viewBox="0 0 512.001 512.001" width="512" xmlns="http://www.w3.org/2000/svg">"#),
+			"Unexpected dump: {dump}",
+		);
+		assert!(dump.contains("\n  <path d=\"M512.001 "), "Unexpected dump: {dump}");
+		assert!(dump.ends_with("\n</svg>\n"), "Unexpected dump: {dump}");
+
+		// Names should be normalized, and text included.
+		let dump = dump_events(r#"<SVG viewBox="0 0 10 10"><TEXT x="1">Hi</TEXT></SVG>"#);
+		assert_eq!(
+			dump,
+			"<svg viewBox=\"0 0 10 10\">\n  <text x=\"1\">\n    #text \"Hi\"\n  </text>\n</svg>\n",
+		);
+
+		// Errors too.
+		let dump = dump_events(r#"<svg viewBox="0 0 10 10"><rect fill=red/></svg>"#);
+		assert!(dump.contains("!error: "), "Missing error: {dump}");
+		assert_eq!(dump_events("nope"), "!error: no <svg> element found\n");
+	}
+
+	#[test]
+	fn test_duplicate_content() {
+		// Identical content under different names — e.g. intentional aliases —
+		// should be kept as distinct symbols.
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
+		let sources = [("i-close", raw), ("i-dismiss", raw)];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(map.len(), 2);
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-close" viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></symbol>"#,
+				r#"<symbol id="i-dismiss" viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></symbol>"#,
+				"</svg>",
+			),
+		);
+	}
+
+	#[test]
+	fn test_duplicate_attrs() {
+		let raw = r#"<svg viewBox="0 0 10 10"><path fill="red" d="M0 0h10v10H0z" fill='blue'/></svg>"#;
+		let (s, warn, _) = parse_svg(raw, Path::new("dupe.svg"), "i-dupe", &MapOptions::default())
+			.expect("Parse failed.");
+		assert_eq!(warn, ContentWarnings::DUPLICATE_ATTRS);
+
+		// The last value should win.
+		let s = s.to_string();
+		assert!(s.contains(r#"fill="blue""#), "Wrong value kept: {s}");
+		assert!(! s.contains(r#"fill="red""#), "Wrong value kept: {s}");
+
+		for (raw, expected) in [
+			(r#" fill="a" fill="b"/>"#, true),
+			(" fill=a stroke fill=b>", true),
+			(r#" fill="a" d="fill='b' fill=c"/>"#, false),
+			(r#" fill="a"> fill="b""#, false),
+			(r#" fill="a" FILL="b">"#, false),
+			("", false),
+		] {
+			assert_eq!(has_duplicate_attrs(raw), expected, "Wrong answer for {raw:?}.");
+		}
+	}
+
+	#[test]
+	fn test_well_formed() {
+		let raw = r#"<svg viewBox="0 0 10 10"><title>Salt & Pepper</title><path d="M0 0h10v10H0z"/></svg>"#;
+
+		// The parser doesn't mind.
+		assert!(parse_svg(raw, Path::new("amp.svg"), "i-amp", &MapOptions::default()).is_ok());
+
+		// But the check does.
+		let opts = MapOptions { check_well_formed: true, ..MapOptions::default() };
+		assert!(matches!(
+			parse_svg(raw, Path::new("amp.svg"), "i-amp", &opts),
+			Err(SvgError::Malformed(_, e)) if e == "unescaped & (line 1)",
+		));
+
+		// The same content, escaped, is fine.
+		let raw = raw.replace(" & ", " &amp; ");
+		assert!(parse_svg(&raw, Path::new("amp.svg"), "i-amp", &opts).is_ok());
+
+		// Good.
+		for raw in [
+			r#"<svg viewBox="0 0 10 10"/>"#,
+			"\u{feff}<?xml version=\"1.0\"?>\n<!-- Hi -->\n<svg a='1' b=\"&#x3c;&#60;&lt;\">\n\t<g/>\n</svg>\n",
+			r#"<!DOCTYPE svg [ <!ENTITY ns "http://x" > ]><svg xmlns="&ns;"><![CDATA[ & < ]]></svg>"#,
+			r#"<svg xml:space="preserve"><text>A&#8212;B</text ></svg>"#,
+		] {
+			assert_eq!(well_formed(raw), Ok(()), "Wrongly rejected: {raw:?}");
+		}
+
+		// Bad.
+		for (raw, expected) in [
+			("<svg>\n<g>\n</svg>", "</svg> does not match <g> (line 3)"),
+			("<svg><g></svg></g>", "</svg> does not match <g> (line 1)"),
+			("<svg>", "unclosed <svg> (line 1)"),
+			("<svg/></g>", "unexpected </g> (line 1)"),
+			("<svg/><svg/>", "multiple root elements (line 1)"),
+			("hello<svg/>", "text outside the root element (line 1)"),
+			("", "no root element (line 1)"),
+			(r#"<svg a="1/>"#, "unterminated a in <svg> (line 1)"),
+			("<svg a=1/>", "unquoted a in <svg> (line 1)"),
+			("<svg a/>", "a has no value in <svg> (line 1)"),
+			(r#"<svg a="1"b="2"/>"#, "missing whitespace in <svg> (line 1)"),
+			(r#"<svg a="1" a="2"/>"#, "duplicate a in <svg> (line 1)"),
+			(r#"<svg a="<"/>"#, "< within a in <svg> (line 1)"),
+			(r#"<svg a="&nbsp;"/>"#, "invalid reference &nbsp; (line 1)"),
+			("<svg>&#0;</svg>", "invalid reference &#0; (line 1)"),
+			("<svg><1a/></svg>", "invalid tag name (line 1)"),
+			(r#"<svg 1a="x"/>"#, "invalid attribute name in <svg> (line 1)"),
+			("<svg><!-- a -- b --></svg>", "-- within a comment (line 1)"),
+			("<svg>\n<!-- a</svg>", "unterminated comment (line 2)"),
+			("<svg>\u{1}</svg>", "illegal character U+0001 (line 1)"),
+			("<![CDATA[x]]><svg/>", "CDATA outside the root element (line 1)"),
+			("<svg/><!DOCTYPE svg>", "misplaced DOCTYPE (line 1)"),
+		] {
+			assert_eq!(well_formed(raw).map_err(|e| e == expected), Err(true), "Wrong answer for {raw:?}: {:?}", well_formed(raw));
+		}
+	}
+
+	#[test]
+	fn test_emit_dimensions() {
+		let paths = [PathBuf::from("test-assets/up.svg")];
+		let mut opts = MapOptions::default();
+
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.").to_string();
+		assert!(! map.contains("data-width"), "Unexpected dimensions: {map}");
+
+		opts.emit_dimensions = true;
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.").to_string();
+		assert!(
+			map.contains(r#"<symbol data-height="280.371" data-width="444.819" id="i-up" viewBox="0 0 444.819 280.371">"#),
+			"Missing dimensions: {map}",
+		);
+	}
+
+	#[test]
+	fn test_error_context() {
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/><rect width="5" fill=red/></svg>"#;
+		let path = Path::new("broken.svg");
+		let mut opts = MapOptions::default();
+
+		// Without context, the error is generic.
+		let Err(e) = parse_svg(raw, path, "i-broken", &opts) else {
+			panic!("BUG: malformed SVG parsed successfully.");
+		};
+		assert_eq!(e.to_string(), r#"Unable to parse: "broken.svg"."#);
+
+		// With context, we should see the offending bits.
+		opts.error_context = true;
+		let Err(e) = parse_svg(raw, path, "i-broken", &opts) else {
+			panic!("BUG: malformed SVG parsed successfully.");
+		};
+		let e = e.to_string();
+		assert!(e.contains(r#"<rect width="5" fill=red/>"#), "BUG: missing context: {e}");
+
+		// Truncated files never make it to the parser, but should still get
+		// some context.
+		let raw = "<svg viewBox=\"0 0 10 10\">\n\t<path d=\"M0 0h10v10H0z\"/>\n\t<circle cx=\"5";
+		let Err(e) = parse_svg(raw, path, "i-broken", &opts) else {
+			panic!("BUG: truncated SVG parsed successfully.");
+		};
+		assert_eq!(
+			e.to_string(),
+			r#"Unable to parse: "broken.svg", near: v10H0z"/> <circle cx="5"#,
+		);
 	}
 
-	// Grab the main element.
-	events.reverse();
-	let mut out = parse_main(events.pop(), path)?
-		.set("id", format!("{prefix}-{stem}"));
+	#[test]
+	fn test_first_chars() {
+		let raw = "\n\n  <!DOCTYPE html>\n<html lang=\"en\">\n\t<head><title>Not an SVG</title></head></html>";
+		let Err(e) = parse_svg(raw, Path::new("page.svg"), "i-page", &MapOptions::default()) else {
+			panic!("BUG: HTML parsed successfully.");
+		};
+		assert_eq!(
+			e.to_string(),
+			r#"Missing <svg> tag: "page.svg"; it begins: <!DOCTYPE html> <html lang="en"> <head><…"#,
+		);
+
+		// Short sources are returned whole.
+		assert_eq!(first_chars("  Hello\r\n  World  "), "Hello World");
+		assert_eq!(first_chars(""), "");
+		assert_eq!(first_chars(&"a".repeat(40)), "a".repeat(40));
+		assert_eq!(first_chars(&format!("{} b", "a".repeat(40))), format!("{}…", "a".repeat(40)));
+
+		// Files with an <svg> still get the generic error.
+		let Err(e) = parse_svg("<svg viewBox=\"0 0 10 10\">", Path::new("open.svg"), "i-open", &MapOptions::default()) else {
+			panic!("BUG: malformed SVG parsed successfully.");
+		};
+		assert_eq!(e.to_string(), r#"Unable to parse: "open.svg"."#);
+	}
+
+	#[test]
+	fn test_fixed_dimensions() {
+		for (raw, expected) in [
+			(r#"<svg width="24px" height="24px" viewBox="0 0 24 24"><path d="M0 0h24v24H0z"/></svg>"#, Some(r#"width="24px" height="24px""#)),
+			(r#"<svg height="2in" WIDTH="100%" viewBox="0 0 24 24"><path d="M0 0h24v24H0z"/></svg>"#, Some(r#"WIDTH="100%" height="2in""#)),
+			(r#"<svg width="24" height="24" viewBox="0 0 24 24"><path d="M0 0h24v24H0z"/></svg>"#, None),
+			(r#"<svg width="1.5em" height="1.5em" viewBox="0 0 24 24"><path d="M0 0h24v24H0z"/></svg>"#, None),
+			(r#"<svg width="24px" height="24px"><path d="M0 0h24v24H0z"/></svg>"#, None),
+		] {
+			assert_eq!(fixed_dimensions(raw).as_deref(), expected, "Fixed dimensions mismatch for {raw:?}.");
+		}
+
+		// It's only checked when asked.
+		let sources = [("i-fixed", r#"<svg width="24px" height="24px" viewBox="0 0 24 24"><path d="M0 0h24v24H0z"/></svg>"#)];
+		let mut opts = MapOptions::default();
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].fixed_dimensions(), None);
+
+		opts.warn_fixed_dimensions = true;
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].fixed_dimensions(), Some(r#"width="24px" height="24px""#));
+
+		// The dimensions still don't make it to the symbol.
+		assert!(! map.to_string().contains("24px"));
+	}
+
+	#[test]
+	fn test_in_memory_sources() {
+		let sources = [
+			("i-square", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-dot", r#"<svg width="4" height="4"><circle cx="2" cy="2" r="2"/></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources)
+			.expect("Map failed.");
+		assert_eq!(map.len(), 2);
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-dot" viewBox="0 0 4 4"><circle cx="2" cy="2" r="2"/></symbol>"#,
+				r#"<symbol id="i-square" viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></symbol>"#,
+				"</svg>",
+			),
+		);
+
+		// Duplicates and empty sets should still fail.
+		assert!(matches!(
+			Map::with_sources(&MapOptions::default(), &[], &[sources[0], sources[0]]),
+			Err(SvgError::Duplicate(_)),
+		));
+		assert!(matches!(
+			Map::with_sources(&MapOptions::default(), &[], &[]),
+			Err(SvgError::NoSvgs),
+		));
+	}
+
+	#[test]
+	fn test_doctype() {
+		let sources = [
+			("i-dot", r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#),
+		];
+		let opts = MapOptions { doctype: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">"#,
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-dot" viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></symbol>"#,
+				"</svg>",
+			),
+		);
+		assert!(map.render(OutputStyle::Pretty).starts_with(concat!(
+			r#"<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">"#, "\n",
+			"<svg ",
+		)));
+
+		// The doctype shouldn't get in the way of comparisons.
+		let symbols = sprite_symbols(&map.to_string()).expect("Unable to read sprite.");
+		assert!(symbols.contains_key("i-dot"));
+
+		// Fragments don't get one.
+		let opts = MapOptions { doctype: true, html_fragment: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert!(map.to_string().starts_with(r#"<div style="display:none" aria-hidden="true"><svg "#));
+		assert!(! map.to_string().contains("DOCTYPE"));
+	}
+
+	#[test]
+	fn test_html_fragment() {
+		let sources = [
+			("i-dot", r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#),
+		];
+		let opts = MapOptions { html_fragment: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<div style="display:none" aria-hidden="true">"#,
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-dot" viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></symbol>"#,
+				"</svg></div>",
+			),
+		);
+		assert_eq!(
+			map.render(OutputStyle::Pretty),
+			concat!(
+				r#"<div style="display:none" aria-hidden="true">"#, "\n",
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#, "\n",
+				"\t", r#"<symbol id="i-dot" viewBox="0 0 10 10">"#, "\n",
+				"\t\t", r#"<circle cx="5" cy="5" r="4"/>"#, "\n",
+				"\t</symbol>\n",
+				"</svg>\n",
+				"</div>",
+			),
+		);
+
+		// The wrapped map should still be readable for comparisons.
+		let symbols = sprite_symbols(&map.to_string()).expect("Unable to read sprite.");
+		assert!(symbols.contains_key("i-dot"));
+	}
+
+	#[test]
+	fn test_inherit_root_presentation() {
+		let raw = r#"<svg viewBox="0 0 10 10" fill="red" stroke-width="2" data-foo="bar"><title>Dot</title><circle cx="5" cy="5" r="4"/><path d="M0 0h1"/></svg>"#;
+
+		// The root presentation is normally lost.
+		let opts = MapOptions::default();
+		let (s, _, _) = parse_svg(raw, Path::new("dot.svg"), "i-dot", &opts).expect("Parse failed.");
+		assert_eq!(
+			s.to_string().replace('\n', ""),
+			r#"<symbol id="i-dot" viewBox="0 0 10 10"><title>Dot</title><circle cx="5" cy="5" r="4"/><path d="M0 0h1"/></symbol>"#,
+		);
+
+		// But can be preserved with a wrapper.
+		let opts = MapOptions { inherit_root_presentation: true, ..MapOptions::default() };
+		let (s, _, _) = parse_svg(raw, Path::new("dot.svg"), "i-dot", &opts).expect("Parse failed.");
+		assert_eq!(
+			s.to_string().replace('\n', ""),
+			concat!(
+				r#"<symbol id="i-dot" viewBox="0 0 10 10"><title>Dot</title>"#,
+				r#"<g fill="red" stroke-width="2"><circle cx="5" cy="5" r="4"/><path d="M0 0h1"/></g>"#,
+				"</symbol>",
+			),
+		);
+
+		// Attributes carried to the symbol itself aren't duplicated.
+		let opts = MapOptions {
+			inherit_root_presentation: true,
+			keep_root_attributes: vec!["fill".to_owned()],
+			..MapOptions::default()
+		};
+		let (s, _, _) = parse_svg(raw, Path::new("dot.svg"), "i-dot", &opts).expect("Parse failed.");
+		assert_eq!(
+			s.to_string().replace('\n', ""),
+			concat!(
+				r#"<symbol fill="red" id="i-dot" viewBox="0 0 10 10"><title>Dot</title>"#,
+				r#"<g stroke-width="2"><circle cx="5" cy="5" r="4"/><path d="M0 0h1"/></g>"#,
+				"</symbol>",
+			),
+		);
+	}
+
+	#[test]
+	fn test_integer_viewbox() {
+		assert_eq!(integer_viewbox("0 0 23.5 24").as_deref(), Some("0 0 24 24"));
+		assert_eq!(integer_viewbox("-1.5,2 10.2,0.3").as_deref(), Some("-1.5 2 10 1"));
+		assert_eq!(integer_viewbox("0 0 24 24"), None);
+		assert_eq!(integer_viewbox("0 0 24.0 24"), None);
+		assert_eq!(integer_viewbox("0 0 wide 24"), None);
+
+		let sources = [
+			("i-fraction", r#"<svg viewBox="0 0 23.5 24"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-whole", r#"<svg viewBox="0 0 24 24"><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+
+		// Left alone by default.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 23.5 24");
+		assert!(map.symbols().iter().all(|s| s.rounded_viewbox().is_none()));
+
+		// Rounded when asked.
+		let opts = MapOptions { integer_viewbox: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 24 24");
+		assert_eq!(map.symbols()[0].rounded_viewbox(), Some("0 0 23.5 24"));
+		assert_eq!(map.symbols()[1].rounded_viewbox(), None);
+		assert!(map.to_string().contains(r#"<symbol id="i-fraction" viewBox="0 0 24 24">"#));
+	}
+
+	#[test]
+	fn test_viewbox_source() {
+		assert_eq!(ViewboxSource::from_name("auto"), Some(ViewboxSource::Auto));
+		assert_eq!(ViewboxSource::from_name("viewbox"), Some(ViewboxSource::Viewbox));
+		assert_eq!(ViewboxSource::from_name(" width-height "), Some(ViewboxSource::WidthHeight));
+		assert_eq!(ViewboxSource::from_name("wh"), None);
+
+		let both = [
+			("i-both", r#"<svg viewBox="0 0 24 24" width="32px" height="16px"><path d="M0 0h1v1H0z"/></svg>"#),
+		];
+		let wh = [
+			("i-wh", r#"<svg width="32" height="16"><path d="M0 0h1v1H0z"/></svg>"#),
+		];
+		let vb = [
+			("i-vb", r#"<svg viewBox="0 0 24 24"><path d="M0 0h1v1H0z"/></svg>"#),
+		];
+
+		// Auto prefers the viewBox, but will fall back.
+		let opts = MapOptions { viewbox_source: ViewboxSource::Auto, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &both).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 24 24");
+		assert!(! map.symbols()[0].derived_viewbox());
+		let map = Map::with_sources(&opts, &[], &wh).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 32 16");
+		assert!(map.symbols()[0].derived_viewbox());
+
+		// Viewbox requires one.
+		let opts = MapOptions { viewbox_source: ViewboxSource::Viewbox, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &both).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 24 24");
+		assert!(matches!(Map::with_sources(&opts, &[], &wh), Err(SvgError::Viewbox(_))));
+
+		// Width-height ignores the viewBox entirely.
+		let opts = MapOptions { viewbox_source: ViewboxSource::WidthHeight, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &both).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 32 16");
+		assert!(map.symbols()[0].derived_viewbox());
+		assert!(map.to_string().contains(r#"<symbol id="i-both" viewBox="0 0 32 16">"#));
+		assert!(matches!(Map::with_sources(&opts, &[], &vb), Err(SvgError::Viewbox(_))));
+	}
+
+	#[test]
+	fn test_precision() {
+		assert_eq!(round_viewbox("0 0 3.333333 4.4444449", 2).as_deref(), Some("0 0 3.33 4.44"));
+		assert_eq!(round_viewbox("0.123 -1.5 3.333333 4.4444449", 0).as_deref(), Some("0.123 -1.5 3 4"));
+		assert_eq!(round_viewbox("0,0,10.26,20.5", 1).as_deref(), Some("0 0 10.3 20.5"));
+		assert_eq!(round_viewbox("0 0 3.5 4.25", 3), None);
+		assert_eq!(round_viewbox("0 0 24 24", 0), None);
+		assert_eq!(round_viewbox("0 0 wide 24", 2), None);
+
+		// Rounding never leaves a dimension at zero.
+		assert_eq!(round_viewbox("0 0 0.004 10.006", 2).as_deref(), Some("0 0 0.004 10.01"));
+		assert_eq!(round_viewbox("0 0 0.4 0.3", 0), None);
+
+		let sources = [
+			("i-long", r#"<svg viewBox="0 0 3.333333 4.4444449"><path d="M0 0h1v1H0z"/></svg>"#),
+		];
+
+		// Left alone by default.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 3.333333 4.4444449");
+
+		// Rounded when asked.
+		let opts = MapOptions { precision: Some(3), ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].viewbox(), "0 0 3.333 4.444");
+		assert!(map.to_string().contains(r#"<symbol id="i-long" viewBox="0 0 3.333 4.444">"#));
+	}
+
+	#[test]
+	fn test_keep_empty() {
+		let raw = r#"<svg viewBox="0 0 10 10"><defs><mask id="m"></mask><marker/></defs><path mask="url(#m)" d="M0 0h10v10H0z"/></svg>"#;
+		let path = Path::new("empty.svg");
+		let mut opts = MapOptions::default();
+
+		// The attribute-less marker should be dropped by default, but the
+		// mask has an ID so should stick around.
+		let (s, _, _) = parse_svg(raw, path, "i-empty", &opts).expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.contains(r#"<mask id="m"/>"#), "Missing mask: {s}");
+		assert!(! s.contains("<marker"), "Marker should have been dropped: {s}");
+
+		// Unless we ask for it to be kept.
+		opts.keep_empty.push(String::from("marker"));
+		let (s, _, _) = parse_svg(raw, path, "i-empty", &opts).expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.contains(r#"<mask id="m"/>"#), "Missing mask: {s}");
+		assert!(s.contains("<marker/>"), "Missing marker: {s}");
+	}
+
+	#[test]
+	fn test_keep_data() {
+		let raw = r#"<svg viewBox="0 0 10 10" data-category="nav" data-Bad="1" data-="2" fill="red"><path data-variant="x" d="M0 0h1"/></svg>"#;
+
+		// Root data attributes are normally lost.
+		let opts = MapOptions::default();
+		let (s, _, _) = parse_svg(raw, Path::new("nav.svg"), "i-nav", &opts).expect("Parse failed.");
+		assert_eq!(
+			s.to_string().replace('\n', ""),
+			r#"<symbol id="i-nav" viewBox="0 0 10 10"><path d="M0 0h1" data-variant="x"/></symbol>"#,
+		);
+
+		// But can be kept, so long as they're well-formed.
+		let opts = MapOptions { keep_data: true, ..MapOptions::default() };
+		let (s, _, _) = parse_svg(raw, Path::new("nav.svg"), "i-nav", &opts).expect("Parse failed.");
+		assert_eq!(
+			s.to_string().replace('\n', ""),
+			r#"<symbol data-category="nav" id="i-nav" viewBox="0 0 10 10"><path d="M0 0h1" data-variant="x"/></symbol>"#,
+		);
+
+		assert!(is_data_attr("data-variant-2"));
+		assert!(! is_data_attr("data-"));
+		assert!(! is_data_attr("data-Variant"));
+		assert!(! is_data_attr("aria-label"));
+	}
+
+	#[test]
+	fn test_keep_root_attributes() {
+		let raw = r#"<svg viewBox="0 0 10 10" width="10" fill="red" stroke="blue" overflow="visible"><path d="M0 0h10v10H0z"/></svg>"#;
+		let path = Path::new("root.svg");
+		let mut opts = MapOptions::default();
+
+		// Nothing extra by default.
+		let (s, _, _) = parse_svg(raw, path, "i-root", &opts).expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.starts_with(r#"<symbol id="i-root" viewBox="0 0 10 10">"#), "Unexpected attributes: {s}");
+
+		// Only the requested attributes should transfer.
+		opts.keep_root_attributes = parse_root_attributes("fill, overflow,opacity")
+			.expect("Invalid root attributes.");
+		let (s, _, _) = parse_svg(raw, path, "i-root", &opts).expect("Parse failed.");
+		let s = s.to_string();
+		assert!(
+			s.starts_with(r#"<symbol fill="red" id="i-root" overflow="visible" viewBox="0 0 10 10">"#),
+			"Unexpected attributes: {s}",
+		);
+
+		// Conflicting and malformed names are rejected.
+		for bad in ["fill,width", "viewBox", "id", "fill=red", "", "1fill"] {
+			assert_eq!(parse_root_attributes(bad), None, "Accepted {bad:?}.");
+		}
+	}
+
+	#[test]
+	fn test_max_depth() {
+		let nested = |depth: usize| {
+			let mut raw = String::from(r#"<svg viewBox="0 0 10 10">"#);
+			for _ in 0..depth { raw.push_str("<g>"); }
+			raw.push_str(r#"<path d="M0 0h10v10H0z"/>"#);
+			for _ in 0..depth { raw.push_str("</g>"); }
+			raw.push_str("</svg>");
+			raw
+		};
+
+		// The root counts as a level.
+		let opts = MapOptions::default();
+		assert!(parse_svg(&nested(255), Path::new("ok.svg"), "i-ok", &opts).is_ok());
+
+		// Something pathological should fail gracefully.
+		let res = parse_svg(&nested(100_000), Path::new("deep.svg"), "i-deep", &opts);
+		assert!(matches!(
+			res,
+			Err(SvgError::NestingTooDeep(p, max)) if p == Path::new("deep.svg") && max.get() == 256,
+		));
+
+		// The limit is configurable.
+		let opts = MapOptions {
+			max_depth: NonZeroUsize::new(3).expect("Non-zero."),
+			..MapOptions::default()
+		};
+		assert!(parse_svg(&nested(2), Path::new("ok.svg"), "i-ok", &opts).is_ok());
+		assert!(parse_svg(&nested(3), Path::new("deep.svg"), "i-deep", &opts).is_err());
+
+		// Stray closing tags shouldn't throw off the count.
+		let stray = "<g></a>".repeat(200_000);
+		let raw = format!(r#"<svg viewBox="0 0 1 1">{stray}</svg>"#);
+		let res = parse_svg(&raw, Path::new("stray.svg"), "i-stray", &MapOptions::default());
+		assert!(matches!(res, Err(SvgError::NestingTooDeep(_, _))));
+
+		// Nor should they be able to sneak past the builders.
+		let mut events: Vec<Event> = Parser::new(&stray).collect();
+		events.reverse();
+		assert!(parse_flat(&mut events, &MapOptions::default(), None, 2).is_none());
+	}
+
+	#[test]
+	fn test_max_id_length() {
+		let paths = [PathBuf::from("test-assets/bitcoin.svg")];
+		let mut opts = MapOptions {
+			max_id_length: NonZeroUsize::new(8),
+			..MapOptions::default()
+		};
+
+		// "i-bitcoin" is nine characters.
+		match Map::with_sources(&opts, &paths, &[]) {
+			Err(SvgError::IdLength(id, _)) => { assert_eq!(id, "i-bitcoin"); },
+			_ => panic!("BUG: expected an ID length error."),
+		}
+
+		opts.max_id_length = NonZeroUsize::new(9);
+		assert!(Map::with_sources(&opts, &paths, &[]).is_ok(), "BUG: nine should be fine.");
+	}
+
+	#[test]
+	fn test_max_total_bytes() {
+		let map = Map::with_sources(&MapOptions::default(), &[PathBuf::from("test-assets/close.svg")], &[])
+			.expect("Map failed.");
+		let len = map.to_string().len();
+
+		// No budget, no problem.
+		assert_eq!(map.render_within(OutputStyle::Compact, None).ok().map(|s| s.len()), Some(len));
+
+		// A generous budget is fine too.
+		assert_eq!(
+			map.render_within(OutputStyle::Compact, NonZeroUsize::new(len)).ok().map(|s| s.len()),
+			Some(len),
+		);
+
+		// But a tiny one isn't.
+		assert!(matches!(
+			map.render_within(OutputStyle::Compact, NonZeroUsize::new(10)),
+			Err(SvgError::SpriteTooLarge(l, max)) if l == len && max.get() == 10,
+		));
+	}
+
+	#[test]
+	fn test_nested_svg() {
+		let raw = r#"<svg viewBox="0 0 20 10"><path d="M0 0h10v10H0z"/><svg x="10" y="0" width="10" height="10" viewBox="0 0 5 5"><circle cx="2.5" cy="2.5" r="2.5"/></svg></svg>"#;
+		let path = Path::new("nested.svg");
+
+		// Inner SVGs pass through as-is, with their own viewBox, etc.
+		let (s, _, _) = parse_svg(raw, path, "i-nested", &MapOptions::default())
+			.expect("Parse failed.");
+		assert_eq!(
+			s.to_string().replace('\n', ""),
+			concat!(
+				r#"<symbol id="i-nested" viewBox="0 0 20 10">"#,
+				r#"<path d="M0 0h10v10H0z"/>"#,
+				r#"<svg height="10" viewBox="0 0 5 5" width="10" x="10" y="0">"#,
+				r#"<circle cx="2.5" cy="2.5" r="2.5"/>"#,
+				"</svg></symbol>",
+			),
+		);
+	}
+
+	#[test]
+	fn test_no_text() {
+		let sources = [
+			("i-label", r#"<svg viewBox="0 0 10 10"><text x="1"><tspan>Hi</tspan></text></svg>"#),
+			("i-blank", r#"<svg viewBox="0 0 10 10"><text x="1"> </text><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+
+		// A warning by default.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(map.warnings().len(), 1);
+		assert_eq!(map.warnings()[0].0, Path::new("i-label"));
+		assert_eq!(map.warnings()[0].1, ContentWarnings::TEXT);
+
+		// An error when disallowed.
+		let opts = MapOptions { error_on: ContentWarnings::TEXT, ..MapOptions::default() };
+		assert!(matches!(
+			Map::with_sources(&opts, &[], &sources),
+			Err(SvgError::Disallowed(list)) if list.len() == 1 && list[0].0 == Path::new("i-label"),
+		));
+	}
+
+	#[test]
+	fn test_normalize_ids_to_prefix() {
+		let raw = r##"<svg viewBox="0 0 10 10" aria-labelledby="t"><title id="t">Pie</title><defs><linearGradient id="grad.1"><stop offset="0"/></linearGradient><clipPath id="c"><rect width="5" height="5"/></clipPath></defs><path id="slice" fill="url(#grad.1)" clip-path="url('#c')" style="stroke:url(#grad.1)" d="M0 0h10v10H0z"/><use href="#slice" xlink:href="#slice"/><use href="#external"/></svg>"##;
+		let opts = MapOptions {
+			keep_root_attributes: vec!["aria-labelledby".to_owned()],
+			normalize_ids_to_prefix: true,
+			..MapOptions::default()
+		};
+		let (s, _, _) = parse_svg(raw, Path::new("pie.svg"), "i-pie", &opts).expect("Parse failed.");
+		assert_eq!(
+			s.to_string().replace('\n', ""),
+			concat!(
+				r#"<symbol aria-labelledby="i-pie__t" id="i-pie" viewBox="0 0 10 10">"#,
+				r#"<title id="i-pie__t">Pie</title>"#,
+				r#"<defs><lineargradient id="i-pie__grad1"><stop offset="0"/></lineargradient>"#,
+				r#"<clippath id="i-pie__c"><rect height="5" width="5"/></clippath></defs>"#,
+				r#"<path clip-path="url(&apos;#i-pie__c&apos;)" d="M0 0h10v10H0z" fill="url(#i-pie__grad1)" id="i-pie__slice" style="stroke:url(#i-pie__grad1)"/>"#,
+				r##"<use href="#i-pie__slice" xlink:href="#i-pie__slice"/>"##,
+				r##"<use href="#external"/>"##,
+				"</symbol>",
+			),
+		);
+
+		// Sanitization collisions are avoided.
+		let raw = r#"<svg viewBox="0 0 10 10"><g id="a.b"><path d="M0 0h1"/></g><g id="ab"><path d="M0 0h1"/></g></svg>"#;
+		let (s, _, _) = parse_svg(raw, Path::new("ab.svg"), "i-ab", &opts).expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.contains(r#"id="i-ab__ab""#));
+		assert!(s.contains(r#"id="i-ab__ab-2""#));
+
+		// Even when everything collides.
+		let mut raw = String::from(r#"<svg viewBox="0 0 10 10">"#);
+		for n in 0..250 {
+			raw.push_str(r#"<g id="ab"#);
+			raw.push_str(&".".repeat(n));
+			raw.push_str(r#""><path d="M0 0h1"/></g>"#);
+		}
+		raw.push_str("</svg>");
+		let (s, _, _) = parse_svg(&raw, Path::new("ab.svg"), "i-ab", &opts).expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.contains(r#"id="i-ab__ab""#));
+		for n in 2..=250 {
+			assert!(s.contains(&format!(r#"id="i-ab__ab-{n}""#)), "Missing suffix {n}.");
+		}
+		assert!(! s.contains(r#"id="i-ab__ab-251""#));
+	}
+
+	#[test]
+	fn test_mode_defs() {
+		assert_eq!(SpriteMode::from_name("symbol"), Some(SpriteMode::Symbol));
+		assert_eq!(SpriteMode::from_name(" defs "), Some(SpriteMode::Defs));
+		assert_eq!(SpriteMode::from_name("g"), None);
+
+		let sources = [
+			("i-dot", r#"<svg viewBox="0 0 10 10"><path d="M0 0h1"/></svg>"#),
+			("i-offset", r#"<svg viewBox="12 -8.5 100 50"><rect x="12" width="5" height="5"/></svg>"#),
+		];
+		let opts = MapOptions { mode: SpriteMode::Defs, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg"><defs>"#,
+				r#"<g id="i-dot"><path d="M0 0h1"/></g>"#,
+				r#"<g id="i-offset" transform="translate(-12 8.5)"><rect height="5" width="5" x="12"/></g>"#,
+				"</defs></svg>",
+			),
+		);
+
+		// Comparisons should still find the images.
+		let symbols = sprite_symbols(&map.to_string()).expect("Unable to read sprite.");
+		assert_eq!(symbols.keys().collect::<Vec<_>>(), ["i-dot", "i-offset"]);
+
+		// The standalone versions are unaffected.
+		let individual = map.individual();
+		assert_eq!(individual.len(), 2);
+		assert!(individual[1].1.contains(r#"viewBox="12 -8.5 100 50""#));
+	}
+
+	#[test]
+	fn test_hide() {
+		assert_eq!(HideType::from_name("display"), Some(HideType::Display));
+		assert_eq!(HideType::from_name(" hidden "), Some(HideType::Hidden));
+		assert_eq!(HideType::from_name("none"), Some(HideType::None));
+		assert_eq!(HideType::from_name("offscreen"), Some(HideType::Offscreen));
+		assert_eq!(HideType::from_name("fixed"), None);
+
+		let sources = [("i-dot", r#"<svg viewBox="0 0 10 10"><path d="M0 0h1"/></svg>"#)];
+		for (hide, expected) in [
+			(HideType::None, r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#),
+			(HideType::Display, r#"<svg aria-hidden="true" style="display:none" xmlns="http://www.w3.org/2000/svg">"#),
+			(HideType::Hidden, r#"<svg aria-hidden="true" hidden xmlns="http://www.w3.org/2000/svg">"#),
+			(HideType::Offscreen, r#"<svg aria-hidden="true" style="position:fixed;top:0;left:-100px;width:1px;height:1px;overflow:hidden" xmlns="http://www.w3.org/2000/svg">"#),
+		] {
+			let opts = MapOptions { hide, ..MapOptions::default() };
+			let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+			assert!(map.to_string().starts_with(expected), "Wrong {hide:?} hiding: {map}");
+		}
+	}
+
+	#[test]
+	fn test_output_style() {
+		let sources = [
+			("i-label", r#"<svg viewBox="0 0 10 10"><g fill="red"><text x="1">Hi</text><path d="M0  0h10
+				v10H0z"/></g></svg>"#),
+		];
+		let opts = MapOptions { hide: HideType::Hidden, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+
+		// Display and compact should match.
+		let compact = map.render(OutputStyle::Compact);
+		assert_eq!(map.to_string(), compact);
+		assert_eq!(
+			compact,
+			concat!(
+				r#"<svg aria-hidden="true" hidden xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-label" viewBox="0 0 10 10"><g fill="red"><text x="1">Hi</text>"#,
+				"<path d=\"M0  0h10\n\t\t\t\tv10H0z\"/></g></symbol></svg>",
+			),
+		);
+
+		// Pretty.
+		assert_eq!(
+			map.render(OutputStyle::Pretty),
+			concat!(
+				r#"<svg aria-hidden="true" hidden xmlns="http://www.w3.org/2000/svg">"#, "\n",
+				"\t", r#"<symbol id="i-label" viewBox="0 0 10 10">"#, "\n",
+				"\t\t", r#"<g fill="red">"#, "\n",
+				"\t\t\t", r#"<text x="1">Hi</text>"#, "\n",
+				"\t\t\t", "<path d=\"M0  0h10\n\t\t\t\tv10H0z\"/>\n",
+				"\t\t</g>\n",
+				"\t</symbol>\n",
+				"</svg>",
+			),
+		);
+
+		// Minified.
+		assert_eq!(
+			map.render(OutputStyle::Minified),
+			concat!(
+				r#"<svg aria-hidden="true" hidden xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-label" viewBox="0 0 10 10"><g fill="red"><text x="1">Hi</text>"#,
+				r#"<path d="M0 0h10 v10H0z"/></g></symbol></svg>"#,
+			),
+		);
+
+		// Mixed content should stay inline, otherwise the indentation would
+		// wind up between the bits of text.
+		let sources = [
+			("i-mixed", r#"<svg viewBox="0 0 10 10"><g><text x="1">A<tspan fill="red">B</tspan>C</text></g></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.render(OutputStyle::Pretty),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#, "\n",
+				"\t", r#"<symbol id="i-mixed" viewBox="0 0 10 10">"#, "\n",
+				"\t\t<g>\n",
+				"\t\t\t", r#"<text x="1">A<tspan fill="red">B</tspan>C</text>"#, "\n",
+				"\t\t</g>\n",
+				"\t</symbol>\n",
+				"</svg>",
+			),
+		);
+
+		// Minification shouldn't touch text or anything whitespace-sensitive.
+		let sources = [
+			("i-keep", "<svg viewBox=\"0 0 10 10\"><script>// hi\nalert(1)</script><style>.a {\n\tfill: red;\n}</style><text x=\" 1 \">A  B</text><g xml:space=\"preserve\" class=\" a  b \"><text x=\" 2\">C  D</text></g></svg>"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.render(OutputStyle::Minified),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-keep" viewBox="0 0 10 10">"#,
+				"<script>// hi\nalert(1)</script><style>.a {\n\tfill: red;\n}</style>",
+				r#"<text x="1">A  B</text>"#,
+				r#"<g class=" a  b " xml:space="preserve"><text x=" 2">C  D</text></g>"#,
+				"</symbol></svg>",
+			),
+		);
+
+		assert_eq!(OutputStyle::from_name("compact"), Some(OutputStyle::Compact));
+		assert_eq!(OutputStyle::from_name(" pretty "), Some(OutputStyle::Pretty));
+		assert_eq!(OutputStyle::from_name("minified"), Some(OutputStyle::Minified));
+		assert_eq!(OutputStyle::from_name("min"), None);
+	}
+
+	#[test]
+	fn test_parse_timeout() {
+		// A deeply-nested (but otherwise valid) image.
+		let depth = 200;
+		let mut raw = String::from(r#"<svg viewBox="0 0 10 10">"#);
+		for _ in 0..depth { raw.push_str(r#"<g fill="red">"#); }
+		raw.push_str(r#"<path d="M0 0h10v10H0z"/>"#);
+		for _ in 0..depth { raw.push_str("</g>"); }
+		raw.push_str("</svg>");
+
+		// Fine without a timeout.
+		let opts = MapOptions::default();
+		assert!(parse_svg(&raw, Path::new("deep.svg"), "i-deep", &opts).is_ok());
+
+		// But not with a tiny one.
+		let opts = MapOptions {
+			parse_timeout: Some(std::time::Duration::from_nanos(1)),
+			..MapOptions::default()
+		};
+		assert!(matches!(
+			parse_svg(&raw, Path::new("deep.svg"), "i-deep", &opts),
+			Err(SvgError::ParseTimeout(p)) if p == Path::new("deep.svg"),
+		));
+	}
+
+	#[test]
+	fn test_no_prefix() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+		let mut opts = MapOptions { prefix: String::new(), ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		let ids: Vec<&str> = map.symbols().iter().map(MapSymbol::id).collect();
+		assert_eq!(ids, ["arrow-1", "close"]);
+
+		// Bare IDs still need to start with a letter.
+		let path = tmp_svg("2048.svg", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#);
+		assert!(matches!(
+			Map::with_sources(&opts, std::slice::from_ref(&path), &[]),
+			Err(SvgError::IdStart(_, id)) if id == "2048",
+		));
+
+		// Unless relaxed.
+		opts.relaxed_ids = true;
+		let map = Map::with_sources(&opts, &[path], &[]).expect("Map failed.");
+		assert_eq!(map.symbols()[0].id(), "2048");
+	}
+
+	#[test]
+	fn test_prefix_case() {
+		// The prefix is used as-is, so mixed-case conventions carry through
+		// to the IDs, templated or otherwise.
+		let paths = [PathBuf::from("test-assets/close.svg")];
+		let mut opts = MapOptions {
+			prefix: String::from("Ico"),
+			..MapOptions::default()
+		};
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert!(map.to_string().contains(r#"<symbol id="Ico-close""#));
+
+		opts.id_template.replace(String::from("{prefix}_{stem}"));
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert!(map.to_string().contains(r#"<symbol id="Ico_close""#));
+	}
+
+	#[test]
+	fn test_prolog() {
+		let opts = MapOptions::default();
+		let plain = parse_svg(
+			include_str!("../test-assets/close.svg"),
+			Path::new("close.svg"),
+			"i-close",
+			&opts,
+		).expect("Parse failed.");
+		let prolog = parse_svg(
+			include_str!("../test-assets/close-prolog.svg"),
+			Path::new("close-prolog.svg"),
+			"i-close",
+			&opts,
+		).expect("Parse failed.");
+		assert_eq!(plain.0.to_string(), prolog.0.to_string());
+		assert_eq!(plain.1, prolog.1);
+	}
+
+	#[test]
+	fn test_reject_external() {
+		let path = tmp_svg(
+			"external.svg",
+			r#"<svg viewBox="0 0 10 10"><image href="https://example.com/foo.png" width="10" height="10"/></svg>"#,
+		);
+		let paths = [path.clone()];
+
+		// It's just a warning by default.
+		let mut opts = MapOptions::default();
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert_eq!(
+			map.warnings(),
+			[(path.clone(), ContentWarnings::EXTERNAL_REFS | ContentWarnings::RASTERS)],
+		);
+
+		// But an error with --reject-external.
+		opts.error_on |= ContentWarnings::EXTERNAL;
+		match Map::with_sources(&opts, &paths, &[]) {
+			Err(SvgError::Disallowed(list)) => {
+				assert_eq!(
+					list,
+					[(path, ContentWarnings::EXTERNAL_REFS | ContentWarnings::RASTERS)],
+				);
+			},
+			_ => panic!("BUG: external resources should have been rejected."),
+		}
+	}
+
+	#[test]
+	fn test_self_closing() {
+		let sources = [
+			("i-box", r#"<svg viewBox="0 0 10 10"><g fill="red"><rect width="10" height="10"/><title>Box</title></g></svg>"#),
+		];
+
+		// Always.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-box" viewBox="0 0 10 10"><g fill="red">"#,
+				r#"<rect height="10" width="10"/><title>Box</title>"#,
+				"</g></symbol></svg>",
+			),
+		);
+
+		// Never.
+		let opts = MapOptions { self_closing: SelfClosing::Never, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-box" viewBox="0 0 10 10"><g fill="red">"#,
+				r#"<rect height="10" width="10"></rect><title>Box</title>"#,
+				"</g></symbol></svg>",
+			),
+		);
+		assert_eq!(
+			map.render(OutputStyle::Pretty),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#, "\n",
+				"\t", r#"<symbol id="i-box" viewBox="0 0 10 10">"#, "\n",
+				"\t\t", r#"<g fill="red">"#, "\n",
+				"\t\t\t", r#"<rect height="10" width="10"></rect>"#, "\n",
+				"\t\t\t<title>Box</title>\n",
+				"\t\t</g>\n",
+				"\t</symbol>\n",
+				"</svg>",
+			),
+		);
+
+		// Names.
+		assert_eq!(SelfClosing::from_name("always"), Some(SelfClosing::Always));
+		assert_eq!(SelfClosing::from_name(" never "), Some(SelfClosing::Never));
+		assert_eq!(SelfClosing::from_name("sometimes"), None);
+	}
+
+	#[test]
+	fn test_symbol_class() {
+		let sources = [
+			("i-dot", r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#),
+			("i-plain", r#"<svg viewBox="0 0 10 10" class="plain"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-same", r#"<svg viewBox="0 0 10 10" class="big icon"><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+		let classes = |opts: &MapOptions| {
+			let map = Map::with_sources(opts, &[], &sources).expect("Map failed.");
+			sprite_symbols(&map.to_string()).expect("Unable to read sprite.")
+				.into_values()
+				.map(|s| s.split_once('>').map_or_else(String::new, |(open, _)| open.to_owned()))
+				.collect::<Vec<_>>()
+		};
+
+		// Every symbol gets the class.
+		let mut opts = MapOptions {
+			symbol_class: Some("icon".to_owned()),
+			..MapOptions::default()
+		};
+		assert_eq!(
+			classes(&opts),
+			[
+				r#"<symbol class="icon" id="i-dot" viewBox="0 0 10 10""#,
+				r#"<symbol class="icon" id="i-plain" viewBox="0 0 10 10""#,
+				r#"<symbol class="icon" id="i-same" viewBox="0 0 10 10""#,
+			],
+		);
+
+		// Kept source classes are merged, without duplication.
+		opts.keep_root_attributes = vec!["class".to_owned()];
+		assert_eq!(
+			classes(&opts),
+			[
+				r#"<symbol class="icon" id="i-dot" viewBox="0 0 10 10""#,
+				r#"<symbol class="plain icon" id="i-plain" viewBox="0 0 10 10""#,
+				r#"<symbol class="big icon" id="i-same" viewBox="0 0 10 10""#,
+			],
+		);
+
+		// Validation.
+		assert_eq!(parse_symbol_class(" icon "), Some("icon".to_owned()));
+		assert_eq!(parse_symbol_class("_icon-2"), Some("_icon-2".to_owned()));
+		assert_eq!(parse_symbol_class("-icon"), Some("-icon".to_owned()));
+		for bad in ["", "-", "--icon", "2x", "-2x", "icon big", "icon.big", "ícon"] {
+			assert_eq!(parse_symbol_class(bad), None, "{bad:?} should be invalid.");
+		}
+	}
+
+	#[test]
+	fn test_split_multi() {
+		let raw = r#"<svg viewBox="0 0 24 24"><title>Icons</title><defs><linearGradient id="g"><stop offset="0" stop-color="red"/></linearGradient></defs><symbol id="star" viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></symbol><g id="heart" fill="url(#g)"><path d="M0 0h24v24H0z"/></g></svg>"#;
+		let paths = [tmp_svg("multi.svg", raw)];
+
+		// By default, the file is just one symbol.
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		assert_eq!(map.len(), 1);
+
+		// Split, each icon gets its own.
+		let opts = MapOptions { split_multi: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-heart" viewBox="0 0 24 24">"#,
+				r#"<defs><lineargradient id="g"><stop offset="0" stop-color="red"/></lineargradient></defs>"#,
+				r#"<g fill="url(#g)"><path d="M0 0h24v24H0z"/></g>"#,
+				"</symbol>",
+				r#"<symbol id="i-star" viewBox="0 0 10 10">"#,
+				r#"<defs><lineargradient id="g"><stop offset="0" stop-color="red"/></lineargradient></defs>"#,
+				r#"<path d="M0 0h10v10H0z"/>"#,
+				"</symbol>",
+				"</svg>",
+			),
+		);
+
+		// Single-icon sources are left alone, as are those with loose content.
+		for (name, raw) in [
+			("single.svg", r#"<svg viewBox="0 0 10 10"><g id="only"><path d="M0 0h10v10H0z"/></g></svg>"#),
+			("loose.svg", r#"<svg viewBox="0 0 10 10"><g id="a"><path d="M0 0h1"/></g><g id="b"><path d="M0 0h2"/></g><path d="M0 0h3"/></svg>"#),
+		] {
+			let paths = [tmp_svg(name, raw)];
+			let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+			assert_eq!(map.len(), 1);
+		}
+	}
+
+	#[test]
+	fn test_sort() {
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
+		let sources = [("i-b", raw), ("i-c", raw), ("i-a", raw)];
+		let ids = |sort: SortMode| {
+			let opts = MapOptions { sort, ..MapOptions::default() };
+			Map::with_sources(&opts, &[], &sources).expect("Map failed.")
+				.symbols()
+				.iter()
+				.map(|s| s.id().to_owned())
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(ids(SortMode::Alpha), ["i-a", "i-b", "i-c"]);
+		assert_eq!(ids(SortMode::AlphaDesc), ["i-c", "i-b", "i-a"]);
+		assert_eq!(ids(SortMode::File), ["i-b", "i-c", "i-a"]);
+
+		// Duplicates are caught regardless of order.
+		let sources = [("i-b", raw), ("i-a", raw), ("i-b", raw)];
+		for sort in [SortMode::Alpha, SortMode::AlphaDesc, SortMode::File] {
+			let opts = MapOptions { sort, ..MapOptions::default() };
+			assert!(matches!(
+				Map::with_sources(&opts, &[], &sources),
+				Err(SvgError::Duplicate(id)) if id == "i-b",
+			));
+		}
+
+		// Names.
+		assert_eq!(SortMode::from_name("alpha"), Some(SortMode::Alpha));
+		assert_eq!(SortMode::from_name(" alpha-desc "), Some(SortMode::AlphaDesc));
+		assert_eq!(SortMode::from_name("file"), Some(SortMode::File));
+		assert_eq!(SortMode::from_name("mtime"), Some(SortMode::Mtime));
+		assert_eq!(SortMode::from_name("usage"), Some(SortMode::Usage));
+		assert_eq!(SortMode::from_name("random"), None);
+	}
+
+	#[test]
+	fn test_sort_usage() {
+		assert_eq!(
+			parse_usage_data("i-a\t3\n\n i-b \t 10 \n"),
+			Ok(BTreeMap::from([("i-a".to_owned(), 3), ("i-b".to_owned(), 10)])),
+		);
+		assert_eq!(parse_usage_data("i-a 3"), Err(1));
+		assert_eq!(parse_usage_data("i-a\t3\ni-b\tlots"), Err(2));
+		assert_eq!(parse_usage_data("i-a\t3\n\ni-a\t4"), Err(3));
+		assert_eq!(parse_usage_data("\t4"), Err(1));
+
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
+		let sources = [
+			("i-a", raw), ("i-b", raw), ("i-c", raw), ("i-d", raw), ("i-e", raw), ("i-f", raw),
+		];
+		let opts = MapOptions {
+			sort: SortMode::Usage,
+			usage: parse_usage_data("i-e\t5\ni-c\t20\ni-a\t5\ni-x\t100\ni-f\t0\n")
+				.expect("Invalid usage data."),
+			..MapOptions::default()
+		};
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		let ids: Vec<&str> = map.symbols().iter().map(MapSymbol::id).collect();
+
+		// Busiest first, ties alphabetical, then the unlisted.
+		assert_eq!(ids, ["i-c", "i-a", "i-e", "i-f", "i-b", "i-d"]);
+	}
+
+	#[test]
+	fn test_parallel_order() {
+		// Enough sources to keep a few threads busy.
+		let paths: Vec<PathBuf> = (0..64)
+			.map(|n| tmp_svg(
+				&format!("parallel-{n:02}.svg"),
+				&format!(r#"<svg viewBox="0 0 {w} {w}"><path d="M0 0h{w}v{w}H0z"/></svg>"#, w = n + 1),
+			))
+			.collect();
 
-	// Check for styles, classes, and IDs that may cause issues.
-	let warn = has_styles(&events);
+		// File order should be preserved, and the output repeatable.
+		let opts = MapOptions { sort: SortMode::File, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		let ids: Vec<String> = map.symbols().iter().map(|s| s.id().to_owned()).collect();
+		let expected: Vec<String> = (0..64).map(|n| format!("i-parallel-{n:02}")).collect();
+		assert_eq!(ids, expected);
+		assert_eq!(map.parse_times().len(), 64);
+		assert!(map.parse_times().iter().zip(&paths).all(|(a, b)| a.0 == *b));
+		for _ in 0..4 {
+			assert_eq!(Map::with_sources(&opts, &paths, &[]).expect("Map failed.").to_string(), map.to_string());
+		}
 
-	// Append the children.
-	while ! events.is_empty() {
-		let next = parse_flat(&mut events)
-			.ok_or_else(|| SvgError::Parse(path.to_path_buf()))?;
-		if ! is_empty(&next) {
-			out.append(next);
+		// The first failure (in source order) should always win.
+		let mut broken = paths;
+		broken[40] = tmp_svg("parallel-broken-b.svg", r#"<svg viewBox="0 0 1 1"><path></svg>"#);
+		broken[9] = tmp_svg("parallel-broken-a.svg", r#"<svg viewBox="0 0 1 1"><path></svg>"#);
+		for _ in 0..4 {
+			assert!(matches!(
+				Map::with_sources(&opts, &broken, &[]),
+				Err(SvgError::Parse(p)) if p == broken[9],
+			));
 		}
 	}
 
-	Ok((out, warn))
-}
+	#[test]
+	fn test_sort_mtime() {
+		use std::time::Duration;
 
-/// # Flatten Next Element.
-///
-/// This returns the next element, recursing as necessary to capture all its
-/// children.
-fn parse_flat(events: &mut Vec<Event>) -> Option<Element> {
-	let next = events.pop()?;
-	match next {
-		// It already is flat!
-		Event::Tag(name, Type::Empty, attrs) => {
-			let mut out = Element::new(name.to_ascii_lowercase());
-			for (k, v) in attrs {
-				out.assign(k, v);
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
+		let paths = [
+			tmp_svg("sort-a.svg", raw),
+			tmp_svg("sort-b.svg", raw),
+			tmp_svg("sort-c.svg", raw),
+		];
+		let set_mtimes = |secs: [u64; 3]| {
+			for (path, secs) in paths.iter().zip(secs) {
+				std::fs::File::options().write(true).open(path)
+					.and_then(|f| f.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)))
+					.expect("Unable to set mtime.");
 			}
-			Some(out)
-		},
-		Event::Tag(name, Type::Start, attrs) =>
-			parse_flat2(name.to_ascii_lowercase(), attrs, events),
-		_ => None,
-	}
-}
+		};
+		let ids = |map: &Map| map.symbols().iter().map(|s| s.id().to_owned()).collect::<Vec<_>>();
 
-/// # Flatten Open Tag.
-///
-/// This builds a flat element beginning from its opening tag and ending with
-/// its closing tag.
-///
-/// If the closing tag is missing, `None` is returned.
-fn parse_flat2(mut name: String, attrs: Attributes, events: &mut Vec<Event>) -> Option<Element> {
-	name.make_ascii_lowercase();
-	let mut out = Element::new(&name);
-	for (k, v) in attrs {
-		out.assign(k, v);
+		let opts = MapOptions { sort: SortMode::Mtime, ..MapOptions::default() };
+
+		// Distinct times should be respected.
+		set_mtimes([300, 100, 200]);
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert_eq!(ids(&map), ["i-sort-b", "i-sort-c", "i-sort-a"]);
+
+		// Identical times should fall back to alphabetical order.
+		set_mtimes([0, 0, 0]);
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert_eq!(ids(&map), ["i-sort-a", "i-sort-b", "i-sort-c"]);
+
+		// And the fallback should be reported.
+		let mut entries: Vec<(Symbol, MapSymbol)> = paths.iter()
+			.map(|p| (
+				Symbol::new(),
+				MapSymbol {
+					id: String::new(),
+					src: p.clone(),
+					viewbox: String::new(),
+					colors: Vec::new(),
+					control_chars: Vec::new(),
+					derived_viewbox: false,
+					fixed_dimensions: None,
+					fonts: Vec::new(),
+					empty: false,
+					mtime: Some(SystemTime::UNIX_EPOCH),
+					rounded_viewbox: None,
+					strokes: Vec::new(),
+					title_mismatch: None,
+					unused_namespaces: Vec::new(),
+					warnings: ContentWarnings::NONE,
+					zero_shapes: Vec::new(),
+				},
+			))
+			.collect();
+		assert!(! sort_mtime(&mut entries), "Identical mtimes should not sort.");
+		entries[1].1.mtime = None;
+		assert!(sort_mtime(&mut entries), "Distinct mtimes should sort.");
 	}
 
-	let mut closed = false;
-	while let Some(event) = events.pop() {
-		match event {
-			// We found the end!
-			Event::Tag(s, Type::End, _) if s.eq_ignore_ascii_case(&name) => {
-				closed = true;
-				break;
-			},
-			// Text just gets added.
-			Event::Text(s) => {
-				let s = s.trim();
-				if ! s.is_empty() {
-					out.append(svg::node::Text::new(s));
-				}
-			},
-			// Such tags are only one level deep.
-			Event::Tag(s, Type::Empty, attrs) => {
-				let mut tmp = Element::new(s.to_ascii_lowercase());
-				for (k, v) in attrs {
-					tmp.assign(k, v);
-				}
-				if ! is_empty(&tmp) { out.append(tmp); }
-			},
-			// Recurse.
-			Event::Tag(s, Type::Start, attrs) => {
-				if let Some(tmp) = parse_flat2(s.to_ascii_lowercase(), attrs, events) {
-					if ! is_empty(&tmp) { out.append(tmp); }
-				}
-			},
-			_ => {},
+	#[test]
+	fn test_id_map() {
+		let map = parse_id_map("Icon_24px_Final_v3\tclose\n\n  arrow-1 \t arrow_left \n").expect("Parse failed.");
+		assert_eq!(map.len(), 2);
+		assert_eq!(map.get("Icon_24px_Final_v3").map(String::as_str), Some("close"));
+		assert_eq!(map.get("arrow-1").map(String::as_str), Some("arrow_left"));
+
+		for (bad, line) in [
+			("a\tb\nc d", 2),
+			("\tb", 1),
+			("a\t", 1),
+			("a\tb c", 1),
+			("a\tb\n\na\tc", 3),
+		] {
+			assert_eq!(parse_id_map(bad), Err(line), "Expected line {line} to fail: {bad:?}");
 		}
+
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+		let mut opts = MapOptions {
+			id_map: parse_id_map("arrow-1\tback").expect("Parse failed."),
+			..MapOptions::default()
+		};
+
+		// Mapped stems are still prefixed; the rest are derived as usual.
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		let ids: Vec<&str> = map.symbols().iter().map(MapSymbol::id).collect();
+		assert_eq!(ids, ["i-back", "i-close"]);
+
+		// Templates apply too.
+		opts.id_template.replace(String::from("{prefix}_{stem}"));
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		let ids: Vec<&str> = map.symbols().iter().map(MapSymbol::id).collect();
+		assert_eq!(ids, ["i_back", "i_close"]);
+
+		// Mapped and derived IDs can collide.
+		opts.id_template = None;
+		opts.id_map = parse_id_map("arrow-1\tclose").expect("Parse failed.");
+		assert!(matches!(Map::with_sources(&opts, &paths, &[]), Err(SvgError::Duplicate(id)) if id == "i-close"));
 	}
 
-	if closed { Some(out) }
-	else { None }
-}
+	#[test]
+	fn test_read_id_comments() {
+		assert_eq!(
+			parse_id_comment("<?xml version=\"1.0\"?>\n<!-- yesvgmap-id: brand-logo -->\n<svg></svg>"),
+			Some("brand-logo"),
+		);
+		assert_eq!(
+			parse_id_comment("<!-- Made by hand. --><!--yesvgmap-id:x--><svg></svg>"),
+			Some("x"),
+		);
+		assert_eq!(parse_id_comment("<!-- Made by hand. --><svg></svg>"), None);
+		assert_eq!(parse_id_comment("<svg><!-- yesvgmap-id: inner --></svg>"), None);
 
-/// # Parse Main.
-///
-/// This parses the outer SVG element, ensuring it has a `viewBox`. If it
-/// doesn't, `None` is returned.
-fn parse_main(event: Option<Event>, path: &Path) -> Result<Symbol, SvgError> {
-	if let Some(Event::Tag(s, Type::Start, a)) = event {
-		if s.eq_ignore_ascii_case("svg") {
-			let mut out = Symbol::new();
+		let raw = r#"<!-- yesvgmap-id: brand-logo -->
+<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
+		let paths = [
+			tmp_svg("Logo_Final_v2.svg", raw),
+			PathBuf::from("test-assets/close.svg"),
+		];
 
-			// Do we have a viewbox?
-			if let Some(vb) = a.get("viewBox").or_else(|| a.get("viewbox")).or_else(|| a.get("VIEWBOX")) {
-				out = out.set("viewBox", vb.clone());
-			}
-			else {
-				let vb = parse_wh(
-					a.get("width").or_else(|| a.get("WIDTH")),
-					a.get("height").or_else(|| a.get("HEIGHT")),
-				)
-					.ok_or_else(|| SvgError::Viewbox(path.to_path_buf()))?;
+		// Ignored by default.
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let ids: Vec<&str> = map.symbols().iter().map(MapSymbol::id).collect();
+		assert_eq!(ids, ["i-LogoFinalv2", "i-close"]);
 
-				out = out.set("viewBox", vb);
-			}
+		// Declared IDs take the place of the stem.
+		let mut opts = MapOptions { read_id_comments: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		let ids: Vec<&str> = map.symbols().iter().map(MapSymbol::id).collect();
+		assert_eq!(ids, ["i-brand-logo", "i-close"]);
 
-			return Ok(out);
-		}
+		// Unless there's an explicit mapping.
+		opts.id_map = parse_id_map("Logo_Final_v2\tlogo").expect("Parse failed.");
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert_eq!(map.symbols()[1].id(), "i-logo");
+
+		// Bad declarations are bad.
+		let paths = [tmp_svg("bad-comment.svg", &raw.replace("brand-logo", "brand logo"))];
+		opts.id_map.clear();
+		assert!(matches!(
+			Map::with_sources(&opts, &paths, &[]),
+			Err(SvgError::IdComment(_, id)) if id == "brand logo",
+		));
 	}
 
-	Err(SvgError::Parse(path.to_path_buf()))
-}
+	#[test]
+	fn test_stdin() {
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
 
-/// # Path Stem to ID.
-///
-/// Take the ASCII alphanumeric and `-` characters from the file stem and
-/// return them for use as an ID suffix.
-fn parse_stem_id(path: &Path) -> Option<Cow<'_, str>> {
-	let mut out = path.file_stem()?.to_string_lossy();
+		// A lone in-memory source is enough.
+		let map = Map::with_sources(&MapOptions::default(), &[], &[("i-stdin", raw)])
+			.expect("Map failed.");
+		assert_eq!(map.len(), 1);
+		assert_eq!(map.symbols()[0].id(), "i-stdin");
 
-	// Reduce to alphanumeric and -.
-	if out.chars().any(|c| c != '-' && ! c.is_ascii_alphanumeric()) {
-		out.to_mut().retain(|c: char| c == '-' || c.is_ascii_alphanumeric());
+		// It can also be combined with files.
+		let map = Map::with_sources(
+			&MapOptions::default(),
+			&[PathBuf::from("test-assets/arrow-1.svg")],
+			&[("i-stdin", raw)],
+		).expect("Map failed.");
+		let ids: Vec<&str> = map.symbols().iter().map(MapSymbol::id).collect();
+		assert_eq!(ids, ["i-arrow-1", "i-stdin"]);
+
+		// But there has to be something.
+		assert!(matches!(
+			Map::with_sources(&MapOptions::default(), &[], &[]),
+			Err(SvgError::NoSvgs),
+		));
+
+		// IDs.
+		assert_eq!(parse_stdin_id(" i-logo_2 ", false), Some("i-logo_2".to_owned()));
+		assert_eq!(parse_stdin_id("2048", false), None);
+		assert_eq!(parse_stdin_id("2048", true), Some("2048".to_owned()));
+		assert_eq!(parse_stdin_id("i logo", false), None);
+		assert_eq!(parse_stdin_id("", true), None);
 	}
 
-	// Return it if we got it.
-	if out.is_empty() { None }
-	else { Some(out) }
-}
+	#[test]
+	fn test_strip_version() {
+		let raw = r#"<svg viewBox="0 0 10 10" version="1.1" baseProfile="tiny"><g version="1.1" fill="red"><path BASEPROFILE="full" d="M0 0h10v10H0z"/></g></svg>"#;
+		let path = Path::new("version.svg");
+		let mut opts = MapOptions {
+			keep_root_attributes: parse_root_attributes("version,baseProfile")
+				.expect("Invalid root attributes."),
+			..MapOptions::default()
+		};
 
-/// # Parse Width/Height.
-///
-/// This attempts to build a `viewBox` value from a `width` and `height`,
-/// returning `None` if either is missing or non-positive.
-fn parse_wh(w: Option<&Value>, h: Option<&Value>) -> Option<String> {
-	let w: &str = w?.trim_matches(|c: char| ! matches!(c, '0'..='9' | '.' | '-'));
-	let h: &str = h?.trim_matches(|c: char| ! matches!(c, '0'..='9' | '.' | '-'));
+		// Without stripping, they're all there.
+		let (s, _, _) = parse_svg(raw, path, "i-version", &opts).expect("Parse failed.");
+		let s = s.to_string();
+		assert!(s.contains(r#"baseProfile="tiny""#), "Missing baseProfile: {s}");
+		assert_eq!(s.matches("version=").count(), 2, "Missing version: {s}");
 
-	// If they're both positive, we're good.
-	if 0.0 < w.parse::<f32>().ok()? && 0.0 < h.parse::<f32>().ok()? {
-		let mut out = String::with_capacity(5 + w.len() + h.len());
-		out.push_str("0 0 ");
-		out.push_str(w);
-		out.push(' ');
-		out.push_str(h);
-		Some(out)
+		// With stripping, they're all gone.
+		opts.strip_version = true;
+		let (s, _, _) = parse_svg(raw, path, "i-version", &opts).expect("Parse failed.");
+		let s = s.to_string().replace('\n', "");
+		assert_eq!(
+			s,
+			r#"<symbol id="i-version" viewBox="0 0 10 10"><g fill="red"><path d="M0 0h10v10H0z"/></g></symbol>"#,
+		);
 	}
-	else { None }
-}
 
-/// # Find Range.
-///
-/// This returns the start byte for the first opening SVG tag and the end byte
-/// of the last closing SVG tag.
-///
-/// There are a few gotchas to be aware of:
-/// * The opening SVG element must have at least one attribute (`<svg>` is not allowed);
-/// * Closing tags cannot have any whitespace (they must be exactly `</svg>`);
-/// * Opening tag attributes cannot include unescaped `>` characters;
-/// * Opening tags must precede closing tags;
-/// * There must be an equal number of opening and closing tags;
-///
-/// If any of the above fail, or an open or close cannot be found, `None` is
-/// returned.
-fn ranges(src: &[u8]) -> Option<(usize, usize)> {
-	/// # Opening Marker.
-	const OPEN: &[u8] = b"<svg ";
+	#[test]
+	fn test_mono() {
+		let sources = [
+			("i-mono", r##"<svg viewBox="0 0 10 10"><defs><linearGradient id="g"><stop offset="0" stop-color="#f00"/></linearGradient><mask id="m"><rect width="10" height="10" fill="white"/></mask></defs><path fill="#F00" stroke="rgb(0, 0, 0)" d="M0 0h5v5H0z"/><path fill="none" stroke="url(#g)" d="M5 5h5v5H5z"/><path style="opacity:.5;FILL:blue;stroke:currentColor" mask="url(#m)" d="M0 0h1"/></svg>"##),
+		];
+		let opts = MapOptions { mono: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-mono" viewBox="0 0 10 10">"#,
+				r##"<defs><lineargradient id="g"><stop offset="0" stop-color="#f00"/></lineargradient>"##,
+				r#"<mask id="m"><rect fill="white" height="10" width="10"/></mask></defs>"#,
+				r#"<path d="M0 0h5v5H0z" fill="currentColor" stroke="currentColor"/>"#,
+				r#"<path d="M5 5h5v5H5z" fill="none" stroke="url(#g)"/>"#,
+				r#"<path d="M0 0h1" mask="url(#m)" style="opacity:.5;FILL:currentColor;stroke:currentColor"/>"#,
+				"</symbol>",
+				"</svg>",
+			),
+		);
+	}
 
-	/// # Closing Marker.
-	const CLOSE: &[u8] = b"</svg>";
+	#[test]
+	fn test_themable() {
+		let sources = [
+			("i-duo", r##"<svg viewBox="0 0 10 10"><path fill="#F00" d="M0 0h5v5H0z"/><path style="opacity:.5;fill:blue" d="M5 5h5v5H5z"/><path stroke="#ff0000" d="M0 0h1"/></svg>"##),
+			("i-mono", r#"<svg viewBox="0 0 10 10"><path fill="red" d="M0 0h10v10H0z"/></svg>"#),
+		];
+		let opts = MapOptions { themable: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			map.to_string(),
+			concat!(
+				r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg">"#,
+				r#"<symbol id="i-duo" viewBox="0 0 10 10">"#,
+				"<!-- Themable colors: icon-color-1 (#ff0000), icon-color-2 (blue). -->",
+				r#"<path d="M0 0h5v5H0z" style="fill:var(--icon-color-1, #F00)"/>"#,
+				r#"<path d="M5 5h5v5H5z" style="opacity:.5;fill:var(--icon-color-2, blue)"/>"#,
+				r#"<path d="M0 0h1" style="stroke:var(--icon-color-1, #ff0000)"/>"#,
+				"</symbol>",
+				r#"<symbol id="i-mono" viewBox="0 0 10 10"><path d="M0 0h10v10H0z" fill="red"/></symbol>"#,
+				"</svg>",
+			),
+		);
 
-	let mut opens: u8 = 0;
-	let mut closes: u8 = 0;
+		// The fallbacks should still be reportable.
+		let opts = MapOptions { themable: true, report_colors: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(map.symbols()[0].colors().len(), 3);
+	}
 
-	let mut start_a: usize = 0;
-	let mut end_a: usize = 0;
+	#[test]
+	fn test_title_mismatch() {
+		let close = include_str!("../test-assets/close.svg");
+		let menu = close.replacen("<path", "<title>Menu</title><path", 1);
+		let good = close.replacen("<path", "<title>Close</title><path", 1);
+		let paths = [tmp_svg("close.svg", &menu), tmp_svg("close-x.svg", &good)];
 
-	// Use a window of 6 to capture the full closure.
-	for (idx, chunk) in src.windows(6).enumerate() {
-		if chunk[0] == b'<' {
-			// It's an end!
-			if chunk.eq_ignore_ascii_case(CLOSE) {
-				// Can't close until we've opened!
-				if closes == opens { return None; }
-				closes += 1;
-				end_a = idx + 6;
-			}
-			// It's a beginning!
-			else if chunk[..5].eq_ignore_ascii_case(OPEN) {
-				if opens == 0 {
-					start_a = idx;
-				}
-				opens += 1;
-			}
+		// Nothing is checked by default.
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		assert!(map.symbols().iter().all(|s| s.title_mismatch().is_none()), "Unexpected mismatch.");
+
+		let opts = MapOptions { warn_title_mismatch: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert_eq!(map.symbols()[0].id(), "i-close");
+		assert_eq!(map.symbols()[0].title_mismatch(), Some("Menu"));
+		assert_eq!(map.symbols()[1].title_mismatch(), None);
+
+		for (title, stem, expected) in [
+			("Close", "close", true),
+			("Arrow (Left)", "arrow-left", true),
+			("Bitcoin", "bitcoin-logo", true),
+			("Menu", "close", false),
+			("Hamburger", "menu", false),
+		] {
+			assert_eq!(title_matches(title, stem), expected, "Wrong answer for {title:?}/{stem:?}.");
 		}
 	}
 
-	// We have to have the same number of opens and closes.
-	if 0 < opens && opens == closes {
-		Some((start_a, end_a))
+	#[test]
+	fn test_viewbox_offset() {
+		let sources = [
+			("i-offset", r#"<svg viewBox="12 8 100 50"><path d="M12 8h100v50H12z"/></svg>"#),
+		];
+		let opts = MapOptions { emit_dimensions: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+
+		// Offsets are kept verbatim, with dimensions from the last two values.
+		assert_eq!(map.symbols()[0].viewbox(), "12 8 100 50");
+		assert_eq!(map.symbols()[0].dimensions(), Some((100.0, 50.0)));
+		assert!(
+			map.to_string().contains(r#"<symbol data-height="50" data-width="100" id="i-offset" viewBox="12 8 100 50">"#),
+			"Offset viewBox mangled: {map}",
+		);
 	}
-	else { None }
-}
 
+	#[test]
+	fn test_zero_shapes() {
+		let sources = [
+			("i-fine", r#"<svg viewBox="0 0 10 10"><rect width="10" height="10"/></svg>"#),
+			("i-zero", r#"<svg viewBox="0 0 10 10"><g><rect width="0" height="10"/><circle r="0.0px"/></g><ellipse rx="1" ry="2"/></svg>"#),
+		];
 
+		// Nothing is recorded by default.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources)
+			.expect("Map failed.");
+		assert!(map.symbols().iter().all(|s| s.zero_shapes().is_empty()), "Unexpected zero shapes.");
 
-#[cfg(test)]
-mod tests {
-	use super::*;
+		let opts = MapOptions { warn_zero_shapes: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert!(map.symbols()[0].zero_shapes().is_empty(), "Unexpected zero shapes.");
+		assert_eq!(map.symbols()[1].zero_shapes(), ["<rect> width", "<circle> r"]);
+	}
+
+	#[test]
+	fn test_strokes() {
+		let sources = [
+			("i-fill", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10z" fill="red"/></svg>"#),
+			("i-none", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10" stroke="none"/></svg>"#),
+			("i-scaled", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10" stroke="red" vector-effect="non-scaling-stroke"/></svg>"#),
+			("i-stroke", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10" stroke="red"/><line x2="10" style="stroke: blue"/></svg>"#),
+		];
+
+		// Nothing is recorded by default.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources)
+			.expect("Map failed.");
+		assert!(map.symbols().iter().all(|s| s.strokes().is_empty()), "Unexpected strokes.");
+
+		let opts = MapOptions { report_strokes: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		for s in &map.symbols()[..3] {
+			assert!(s.strokes().is_empty(), "Unexpected strokes for {}.", s.id());
+		}
+		assert_eq!(map.symbols()[3].strokes(), ["path", "line"]);
+	}
 
 	#[test]
 	fn test_hiddentrue() {
@@ -488,12 +5439,92 @@ mod tests {
 		assert_eq!(raw, "<div hidden></div>");
 	}
 
+	#[test]
+	fn test_is_reserved_prefix() {
+		for p in ["symbol", "use", "svg", "clipPath", "G"] {
+			assert!(is_reserved_prefix(p), "Expected {p:?} to be reserved.");
+		}
+		for p in ["i", "icon", "symbols", "nav", ""] {
+			assert!(! is_reserved_prefix(p), "Expected {p:?} to be allowed.");
+		}
+	}
+
+	#[test]
+	fn test_parse_id_template() {
+		let path = Path::new("/icons/Brand Marks/logo.svg");
+		let raw = "<svg/>";
+		let digest = format!("{:016x}", hash64(raw.as_bytes()));
+		let digest = &digest[..HASH_LEN_MIN];
+		for (tpl, expected) in [
+			("{prefix}-{stem}", Some(String::from("i-logo"))),
+			("{prefix}_{dir}_{stem}", Some(String::from("i_BrandMarks_logo"))),
+			("{stem}-{hash}", Some(format!("logo-{digest}"))),
+			("icon.{stem}!", Some(String::from("iconlogo"))),
+			("{hash}", None), // Might start with a number.
+			("-{stem}", None),
+			("{nope}", None),
+		] {
+			assert_eq!(
+				parse_id_template(tpl, "i", path, "logo", raw, false, HASH_LEN_MIN),
+				expected,
+				"BUG: wrong ID for template {tpl:?}.",
+			);
+		}
+
+		// Relaxed IDs can begin with digits or underscores, but not dashes.
+		let path = Path::new("/icons/2048-game.svg");
+		for (tpl, strict, relaxed) in [
+			("{stem}", None, Some("2048-game")),
+			("_{stem}", None, Some("_2048-game")),
+			("-{stem}", None, None),
+			("{prefix}-{stem}", Some("i-2048-game"), Some("i-2048-game")),
+		] {
+			assert_eq!(
+				parse_id_template(tpl, "i", path, "2048-game", raw, false, HASH_LEN_MIN).as_deref(),
+				strict,
+				"BUG: wrong strict ID for template {tpl:?}.",
+			);
+			assert_eq!(
+				parse_id_template(tpl, "i", path, "2048-game", raw, true, HASH_LEN_MIN).as_deref(),
+				relaxed,
+				"BUG: wrong relaxed ID for template {tpl:?}.",
+			);
+		}
+
+		// Make sure the hash is stable.
+		assert_eq!(hash64(b"hello world"), 0x779a_65e7_023c_d2e7);
+	}
+
+	#[test]
+	fn test_parse_output_prefix() {
+		for (raw, expected) in [
+			("sprite-nav.svg", Some("nav")),
+			("/foo/bar/SPRITE-footer.svg", Some("footer")),
+			("icons.svg", Some("icons")),
+			("sprite-.svg", Some("sprite-")),
+			("sprite-1.svg", None),
+			("123.svg", None),
+		] {
+			assert_eq!(
+				parse_output_prefix(raw.as_ref(), false).as_deref(),
+				expected,
+				"BUG: wrong prefix for {raw:?}.",
+			);
+		}
+
+		// Leading digits are fine when relaxed.
+		assert_eq!(parse_output_prefix("sprite-1.svg".as_ref(), true).as_deref(), Some("1"));
+	}
+
 	#[test]
 	fn test_parse_stem_id() {
 		for (raw, expected, borrowed) in [
 			("image.svg", Some("image"), true),
 			("image name.svg", Some("imagename"), false),
 			("ImAgE.svg", Some("ImAgE"), true),
+			("-close-.svg", Some("-close-"), true), // Dashes are kept as-is.
+			(" -close- .svg", Some("-close-"), false),
+			("café-crème.svg", Some("caf-crme"), false), // Non-ASCII is dropped.
 			("__.svg", None, true),
 		] {
 			if let Some(expected) = expected {
@@ -519,11 +5550,16 @@ mod tests {
 	#[test]
 	#[expect(clippy::type_complexity, reason = "It is what it is.")]
 	fn test_ranges() {
-		let tests: [(&[u8], Option<(usize, usize)>); 4] = [
+		let tests: [(&[u8], Option<(usize, usize)>); 9] = [
 			(include_bytes!("../test-assets/close.svg"), Some((0, 287))),
 			(b"<svg id=foo><svg id=bar></svg></svg>", Some((0, 36))),
 			(b"    <SVG id=foo><svg id=bar></svg></svg>", Some((4, 40))),
 			(b"<svg id=foo><svg id=bar></svg>", None),
+			(b"<svg>\n</svg>", Some((0, 12))),
+			(b"<svg\n\tid=foo></svg>", Some((0, 19))),
+			(b"<svgz id=foo></svg>", None),
+			(b"<!-- <svg id=foo> --><svg id=bar></svg>", Some((21, 39))),
+			(b"<!DOCTYPE svg [ <!ENTITY x \"<svg id=foo>\"> ]><svg id=bar></svg>", Some((45, 63))),
 		];
 
 		for (src, expected) in tests {
@@ -531,6 +5567,32 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_unique_id() {
+		// A controlled "hash" so we can force collisions.
+		let render = |len: usize| Some(format!("i-{}", &"0123456789abcdef"[..len]));
+
+		// No collision, no change.
+		assert_eq!(unique_id(render, |_| false).as_deref(), Some("i-01234567"));
+
+		// A short collision should be lengthened.
+		assert_eq!(
+			unique_id(render, |id| id == "i-01234567").as_deref(),
+			Some("i-0123456789ab"),
+		);
+		assert_eq!(
+			unique_id(render, |id| id.len() < 18).as_deref(),
+			Some("i-0123456789abcdef"),
+		);
+
+		// But only so far.
+		assert_eq!(unique_id(render, |_| true).as_deref(), Some("i-0123456789abcdef"));
+
+		// Hashless IDs are what they are.
+		assert_eq!(unique_id(|_| Some(String::from("i-logo")), |_| true).as_deref(), Some("i-logo"));
+		assert_eq!(unique_id(|_| None, |_| false), None);
+	}
+
 	#[test]
 	fn test_wh() {
 		let tests: [(Option<Value>, Option<Value>, Option<String>); 6] = [
@@ -571,7 +5633,7 @@ mod tests {
 		}
 	}
 
-	fn has_styles_wrapper(raw: &str) -> bool {
+	fn has_styles_wrapper(raw: &str) -> ContentWarnings {
 		// Find the start and end ranges.
 		let (start, end) = ranges(raw.as_bytes())
 			.expect("Failed to parse SVG.");
@@ -597,29 +5659,97 @@ mod tests {
 		events.pop();
 
 		// Actually check the styles!
-		has_styles(&events)
+		content_warnings(&events)
 	}
 
 	#[test]
 	fn test_styles() {
-		assert!(
-			has_styles_wrapper(include_str!("../test-assets/arrow-1.svg")),
-			"Missed styles for arrow-1.svg."
-		);
+		for (raw, expected) in [
+			(
+				include_str!("../test-assets/arrow-1.svg"),
+				ContentWarnings::STYLES | ContentWarnings::CLASSES,
+			),
+			(include_str!("../test-assets/arrow-2.svg"), ContentWarnings::CLASSES),
+			(include_str!("../test-assets/arrow-3.svg"), ContentWarnings::IDS),
+			(include_str!("../test-assets/bitcoin.svg"), ContentWarnings::NONE),
+			(
+				r#"<svg viewBox="0 0 10 10"><image href="https://example.com/a.png"/><path onclick="alert(1)" d="M0 0h10v10H0z"/></svg>"#,
+				ContentWarnings::RASTERS | ContentWarnings::EXTERNAL_REFS | ContentWarnings::SCRIPTS,
+			),
+			(
+				r##"<svg viewBox="0 0 10 10"><image xlink:href="data:image/png;base64,AAAA"/><use href="#foo"/></svg>"##,
+				ContentWarnings::RASTERS | ContentWarnings::DATA_URIS,
+			),
+		] {
+			assert_eq!(has_styles_wrapper(raw), expected, "Wrong warnings for {raw}.");
+		}
+	}
 
-		assert!(
-			has_styles_wrapper(include_str!("../test-assets/arrow-2.svg")),
-			"Missed styles for arrow-2.svg."
-		);
+	#[test]
+	fn test_error_on() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/arrow-3.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
 
-		assert!(
-			has_styles_wrapper(include_str!("../test-assets/arrow-3.svg")),
-			"Missed styles for arrow-3.svg."
-		);
+		// Scripts and external references are fine.
+		let mut opts = MapOptions {
+			error_on: ContentWarnings::SCRIPTS | ContentWarnings::EXTERNAL_REFS,
+			..MapOptions::default()
+		};
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		assert_eq!(map.warnings().len(), 2);
 
-		assert!(
-			!has_styles_wrapper(include_str!("../test-assets/bitcoin.svg")),
-			"False positive styles for bitcoin.svg."
-		);
+		// But IDs aren't!
+		opts.error_on |= ContentWarnings::IDS;
+		match Map::with_sources(&opts, &paths, &[]) {
+			Err(SvgError::Disallowed(list)) => {
+				assert_eq!(list, [(paths[1].clone(), ContentWarnings::IDS)]);
+			},
+			_ => panic!("BUG: IDs should have been disallowed."),
+		}
+	}
+
+	#[test]
+	fn test_strict() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-3.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+
+		// Every content warning becomes an error.
+		let mut opts = MapOptions { strict: true, ..MapOptions::default() };
+		match Map::with_sources(&opts, &paths, &[]) {
+			Err(SvgError::Disallowed(list)) => {
+				assert_eq!(list, [(paths[0].clone(), ContentWarnings::IDS)]);
+				let msg = SvgError::Disallowed(list).to_string();
+				assert!(msg.contains("arrow-3.svg\" \x1b[2m(id)"), "Missing categories: {msg}");
+			},
+			_ => panic!("BUG: IDs should have been disallowed."),
+		}
+		assert!(Map::with_sources(&opts, &paths[1..], &[]).is_ok(), "Clean sources should pass.");
+
+		// The optional diagnostics are errors too, but only when enabled.
+		let sources = [
+			("i-zero", r#"<svg viewBox="0 0 23.5 24"><rect width="0" height="10"/><path d="M0 0h1"/></svg>"#),
+		];
+		assert!(Map::with_sources(&opts, &[], &sources).is_ok(), "Nothing should have been checked.");
+
+		opts.warn_zero_shapes = true;
+		opts.integer_viewbox = true;
+		match Map::with_sources(&opts, &[], &sources) {
+			Err(SvgError::StrictWarnings(list)) => {
+				assert_eq!(list, [
+					("i-zero".to_owned(), "zero-dimension shapes: <rect> width".to_owned()),
+					("i-zero".to_owned(), "fractional viewBox: 0 0 23.5 24".to_owned()),
+				]);
+			},
+			_ => panic!("BUG: Strict diagnostics should have failed."),
+		}
+
+		// Without strict, they're just noted.
+		opts.strict = false;
+		assert!(Map::with_sources(&opts, &[], &sources).is_ok(), "Diagnostics should only warn.");
 	}
 }
@@ -0,0 +1,162 @@
+/*!
+# Yesvgmap: Logging
+*/
+
+use fyi_msg::{
+	Msg,
+	MsgKind,
+};
+use std::{
+	fmt,
+	sync::atomic::{
+		AtomicU8,
+		Ordering::Relaxed,
+	},
+};
+
+
+
+/// # Environment Variable.
+///
+/// The log level can be set with this instead of `--log-level`.
+pub(super) const LOG_ENV: &str = "YESVGMAP_LOG";
+
+/// # Current Level.
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+#[cfg(test)]
+thread_local! {
+	/// # Captured Lines.
+	///
+	/// Tests collect log lines here instead of printing them.
+	static CAPTURE: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+
+
+#[derive(Debug, Clone, Copy, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[repr(u8)]
+/// # Log Level.
+///
+/// Messages are printed if their level is at or below the current one, e.g.
+/// `Debug` prints everything.
+pub(super) enum LogLevel {
+	/// # Errors Only.
+	Error = 0,
+
+	/// # Warnings.
+	Warn = 1,
+
+	#[default]
+	/// # General Information.
+	Info = 2,
+
+	/// # Detailed Tracing.
+	Debug = 3,
+}
+
+impl LogLevel {
+	/// # From Name.
+	///
+	/// Return the level corresponding to the (CLI or environment) name, if
+	/// any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim().to_ascii_lowercase().as_str() {
+			"error" => Some(Self::Error),
+			"warn" | "warning" => Some(Self::Warn),
+			"info" => Some(Self::Info),
+			"debug" => Some(Self::Debug),
+			_ => None,
+		}
+	}
+
+	/// # Message Kind.
+	const fn kind(self) -> MsgKind {
+		match self {
+			Self::Error => MsgKind::Error,
+			Self::Warn => MsgKind::Warning,
+			Self::Info => MsgKind::Info,
+			Self::Debug => MsgKind::Debug,
+		}
+	}
+}
+
+
+
+/// # Set Level.
+pub(super) fn set_level(level: LogLevel) { LEVEL.store(level as u8, Relaxed); }
+
+/// # Enabled?
+///
+/// Returns `true` if messages of the given level would be printed.
+pub(super) fn enabled(level: LogLevel) -> bool { level as u8 <= LEVEL.load(Relaxed) }
+
+/// # Log.
+///
+/// Print a message to STDERR, if its level is enabled. The arguments are only
+/// formatted if so.
+pub(super) fn log(level: LogLevel, args: fmt::Arguments<'_>) {
+	if enabled(level) {
+		let msg = Msg::new(level.kind(), args.to_string()).with_newline(true);
+
+		#[cfg(test)]
+		CAPTURE.with_borrow_mut(|c| c.push(msg.as_str().to_owned()));
+
+		#[cfg(not(test))]
+		msg.eprint();
+	}
+}
+
+/// # Log (Debug).
+///
+/// A shorthand for [`log`] with [`LogLevel::Debug`].
+pub(super) fn debug(args: fmt::Arguments<'_>) { log(LogLevel::Debug, args); }
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		img::Map,
+		MapOptions,
+	};
+
+	#[test]
+	fn test_levels() {
+		assert_eq!(LogLevel::from_name("error"), Some(LogLevel::Error));
+		assert_eq!(LogLevel::from_name(" WARN "), Some(LogLevel::Warn));
+		assert_eq!(LogLevel::from_name("warning"), Some(LogLevel::Warn));
+		assert_eq!(LogLevel::from_name("info"), Some(LogLevel::Info));
+		assert_eq!(LogLevel::from_name("Debug"), Some(LogLevel::Debug));
+		assert_eq!(LogLevel::from_name("trace"), None);
+
+		assert!(LogLevel::Error < LogLevel::Warn);
+		assert!(LogLevel::Info < LogLevel::Debug);
+		assert_eq!(LogLevel::default(), LogLevel::Info);
+	}
+
+	#[test]
+	fn test_debug() {
+		set_level(LogLevel::Debug);
+		let sources = [
+			("i-b", r#"<svg width="10" height="10"><path class="x" d="M0 0h10v10H0z"/></svg>"#),
+			("i-a", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+		Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+
+		let lines = CAPTURE.take();
+		for expected in [
+			"i-b: parsed 3 events.",
+			"i-b: derived the viewBox from the width and height.",
+			"i-b: content warnings: class.",
+			"i-a: parsed 3 events.",
+			"Sorting 2 symbols by alpha.",
+		] {
+			assert!(
+				lines.iter().any(|l| l.starts_with("\x1b[96;1mDebug:\x1b[0m ") && l.trim_end().ends_with(expected)),
+				"Missing log line {expected:?}: {lines:#?}",
+			);
+		}
+	}
+}
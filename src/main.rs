@@ -33,7 +33,6 @@
 	clippy::rest_pat_in_fully_bound_structs,
 	clippy::semicolon_inside_block,
 	clippy::str_to_string,
-	clippy::string_to_string,
 	clippy::todo,
 	clippy::undocumented_unsafe_blocks,
 	clippy::unneeded_field_pattern,
@@ -57,6 +56,11 @@
 
 mod error;
 mod img;
+mod log;
+mod opts;
+mod report;
+mod selftest;
+mod warning;
 
 
 
@@ -68,14 +72,54 @@ use dowser::{
 };
 pub(crate) use error::SvgError;
 use fyi_msg::Msg;
+use libdeflater::{
+	CompressionLvl,
+	Compressor,
+};
 use img::{
+	dump_events,
 	HideType,
+	is_reserved_prefix,
 	Map,
+	OutputStyle,
+	parse_id_map,
+	parse_output_prefix,
+	parse_root_attributes,
+	parse_stdin_id,
+	parse_symbol_class,
+	parse_usage_data,
+	SelfClosing,
+	SortMode,
+	SpriteMode,
+	sprite_symbols,
+	ViewboxSource,
+};
+use log::{
+	LOG_ENV,
+	LogLevel,
 };
+use opts::MapOptions;
+use report::HrefStyle;
 use std::{
-	borrow::Cow,
-	path::PathBuf,
+	fs::File,
+	io::{
+		ErrorKind,
+		Write,
+	},
+	num::{
+		NonZeroU64,
+		NonZeroUsize,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
+	time::{
+		Duration,
+		Instant,
+	},
 };
+pub(crate) use warning::ContentWarnings;
 
 
 
@@ -84,6 +128,80 @@ include!(concat!(env!("OUT_DIR"), "/yesvgmap-extensions.rs"));
 
 
 
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # Output Mode.
+///
+/// This determines how the map (and its gzipped copy) are written to disk.
+enum OutputMode {
+	#[default]
+	/// # Atomic.
+	///
+	/// Write to a temporary file and rename it into place, so readers never
+	/// see a partial map.
+	Atomic,
+
+	/// # Truncate.
+	///
+	/// Overwrite the existing file in place, keeping its inode, for FIFOs and
+	/// watchers that follow the original file.
+	Truncate,
+}
+
+/// # Stage Timings.
+///
+/// This holds the start time and select stage durations for `--timing`.
+struct Timings {
+	/// # Start.
+	start: Instant,
+
+	/// # Crawl Time.
+	crawl: Duration,
+
+	/// # Serialization Time.
+	serialize: Duration,
+}
+
+impl Timings {
+	/// # New.
+	fn new() -> Self {
+		Self { start: Instant::now(), crawl: Duration::ZERO, serialize: Duration::ZERO }
+	}
+
+	/// # Report.
+	///
+	/// Return the timing summary.
+	fn report(&self, map: &Map) -> String {
+		report::timing(map, self.crawl, self.serialize, self.start.elapsed())
+	}
+}
+
+impl OutputMode {
+	/// # From Name.
+	///
+	/// Return the mode corresponding to the (CLI) name, if any.
+	fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"atomic" => Some(Self::Atomic),
+			"truncate" => Some(Self::Truncate),
+			_ => None,
+		}
+	}
+
+	/// # Write.
+	///
+	/// Save the data to the path.
+	fn write(self, path: &Path, data: &[u8]) -> Result<(), SvgError> {
+		match self {
+			Self::Atomic => write_atomic::write_file(path, data).map_err(|_| SvgError::Write),
+			Self::Truncate => File::create(path)
+				.and_then(|mut f| f.write_all(data).and_then(|()| f.flush()))
+				.map_err(|_| SvgError::Write),
+		}
+	}
+}
+
+
+
 /// # Main.
 fn main() {
 	match main__() {
@@ -94,6 +212,8 @@ fn main() {
 }
 
 #[inline]
+#[expect(clippy::too_many_lines, reason = "There are a lot of CLI options to parse.")]
+#[expect(clippy::unnecessary_debug_formatting, reason = "Saved paths are quoted.")]
 /// # Actual main.
 ///
 /// Do our work here so we can easily bubble up errors and handle them nice and
@@ -103,33 +223,256 @@ fn main__() -> Result<(), SvgError> {
 	let args = argyle::args()
 		.with_keywords(include!(concat!(env!("OUT_DIR"), "/argyle.rs")));
 
-	let mut class = None;
-	let mut hide = HideType::None;
-	let mut id = None;
+	// Set the log level from the environment, if present; --log-level can
+	// override it.
+	if let Some(level) = std::env::var(LOG_ENV).ok().and_then(|s| LogLevel::from_name(&s)) {
+		log::set_level(level);
+	}
+
+	let mut opts = MapOptions::default();
+	let mut allow_scripts = false;
+	let mut compare = None;
+	let mut demo = None;
+	let mut href_style = HrefStyle::Href;
+	let mut also_individual = None;
+	let mut css = None;
+	let mut exclude = Vec::new();
+	let mut timings = None;
+	let mut dry_run = false;
+	let mut dump_ast = false;
+	let mut fail_on_diff = false;
+	let mut gzip = false;
+	let mut if_changed = false;
+	let mut id_lock = None;
+	let mut max_total_bytes = None;
+	let mut output_mode = OutputMode::Atomic;
+	let mut output_style = OutputStyle::Compact;
+	let mut max_warnings = None;
 	let mut out = None;
 	let mut paths = Dowser::default();
-	let mut prefix = Cow::Borrowed("i");
+	let mut prefix = None;
+	let mut no_prefix = false;
+	let mut prefix_from_output = false;
+	let mut report = None;
+	let mut report_by_dir = false;
+	let mut report_derived_viewbox = false;
+	let mut report_empty = false;
+	let mut report_paths = false;
+	let mut stdin = None;
+	let mut used_ids = None;
+	let mut verbose = false;
+	let mut warn_odd_sizes = false;
 	for arg in args {
 		match arg {
+			Argument::Key("--allow-scripts") => { allow_scripts = true; },
+			Argument::Key("--check-well-formed") => { opts.check_well_formed = true; },
+			Argument::Key("--clean-attr-whitespace") => { opts.clean_attr_whitespace = true; },
+			Argument::Key("--doctype") => { opts.doctype = true; },
+			Argument::Key("--dry-run") => { dry_run = true; },
+			Argument::Key("--dump-ast") => { dump_ast = true; },
+			Argument::Key("--emit-dimensions") => { opts.emit_dimensions = true; },
+			Argument::Key("--error-context") => { opts.error_context = true; },
+			Argument::Key("--fail-on-diff") => { fail_on_diff = true; },
+			Argument::Key("--gzip") => { gzip = true; },
+			Argument::Key("--if-changed") => { if_changed = true; },
 			Argument::Key("-h" | "--help") => return Err(SvgError::PrintHelp),
-			Argument::Key("--hidden") => { hide = HideType::Hidden; },
-			Argument::Key("--offscreen") => { hide = HideType::Offscreen; },
+			Argument::Key("--hidden") => { opts.hide = HideType::Hidden; },
+			Argument::Key("--html-fragment") => { opts.html_fragment = true; },
+			Argument::Key("--inherit-root-presentation") => { opts.inherit_root_presentation = true; },
+			Argument::Key("--integer-viewbox") => { opts.integer_viewbox = true; },
+			Argument::Key("--minify") => { opts.minify = true; },
+			Argument::Key("--mono") => { opts.mono = true; },
+			Argument::Key("--no-text") => { opts.error_on |= ContentWarnings::TEXT; },
+			Argument::Key("--namespace-ids" | "--normalize-ids-to-prefix") => { opts.normalize_ids_to_prefix = true; },
+			Argument::Key("--offscreen") => { opts.hide = HideType::Offscreen; },
+			Argument::Key("--no-prefix") => { no_prefix = true; },
+			Argument::Key("--prefix-from-output") => { prefix_from_output = true; },
+			Argument::Key("--reject-external") => {
+				opts.error_on |= ContentWarnings::EXTERNAL;
+			},
+			Argument::Key("--read-id-comments") => { opts.read_id_comments = true; },
+			Argument::Key("--relaxed-ids") => { opts.relaxed_ids = true; },
+			Argument::Key("--report-by-dir") => { report_by_dir = true; },
+			Argument::Key("--report-colors") => { opts.report_colors = true; },
+			Argument::Key("--keep-data") => { opts.keep_data = true; },
+			Argument::Key("--report-fonts") => { opts.report_fonts = true; },
+			Argument::Key("--report-strokes") => { opts.report_strokes = true; },
+			Argument::Key("--report-control-chars") => { opts.report_control_chars = true; },
+			Argument::Key("--report-unused-namespaces") => { opts.report_unused_namespaces = true; },
+			Argument::Key("--report-derived-viewbox") => { report_derived_viewbox = true; },
+			Argument::Key("--report-empty") => { report_empty = true; },
+			Argument::Key("--report-paths") => { report_paths = true; },
+			Argument::Key("--report-schema") => {
+				print!("{}", report::json_report_schema());
+				return Ok(());
+			},
+			Argument::Key("--themable") => { opts.themable = true; },
+			Argument::Key("--trim-path-whitespace") => { opts.trim_path_whitespace = true; },
+			Argument::Key("--split-multi") => { opts.split_multi = true; },
+			Argument::Key("--strict") => { opts.strict = true; },
+			Argument::Key("--strip-version") => { opts.strip_version = true; },
+			Argument::Key("--timing") => { timings.replace(Timings::new()); },
+			Argument::Key("--selftest") => return selftest::selftest(),
+			Argument::Key("-v" | "--verbose") => { verbose = true; },
 			Argument::Key("-V" | "--version") => return Err(SvgError::PrintVersion),
+			Argument::Key("--warn-odd-sizes") => { warn_odd_sizes = true; },
+			Argument::Key("--warn-fixed-dimensions") => { opts.warn_fixed_dimensions = true; },
+			Argument::Key("--warn-title-mismatch") => { opts.warn_title_mismatch = true; },
+			Argument::Key("--warn-zero-shapes") => { opts.warn_zero_shapes = true; },
+			Argument::Key("--warnings-summary") => { opts.warnings_summary = true; },
 
+			Argument::KeyWithValue("--attribute-order", s) => {
+				opts.attribute_order.extend(
+					s.split(',')
+						.map(str::trim)
+						.filter(|t| ! t.is_empty())
+						.map(String::from)
+				);
+			},
+			Argument::KeyWithValue("--compare", s) => { compare.replace(PathBuf::from(s)); },
+			Argument::KeyWithValue("--also-individual", s) => { also_individual.replace(PathBuf::from(s)); },
+			Argument::KeyWithValue("--css", s) => { css.replace(PathBuf::from(s)); },
+			Argument::KeyWithValue("--demo", s) => { demo.replace(PathBuf::from(s)); },
+			Argument::KeyWithValue("--error-on", s) => {
+				opts.error_on |= ContentWarnings::from_list(&s)
+					.ok_or(SvgError::CliValue("--error-on", s))?;
+			},
+			Argument::KeyWithValue("--exclude", s) => {
+				exclude.push(parse_exclude(&s).ok_or(SvgError::CliValue("--exclude", s))?);
+			},
+			Argument::KeyWithValue("--hide", s) => {
+				opts.hide = HideType::from_name(&s)
+					.ok_or(SvgError::CliValue("--hide", s))?;
+			},
+			Argument::KeyWithValue("--id-lock", s) => { id_lock.replace(PathBuf::from(s)); },
+			Argument::KeyWithValue("--id-map", s) => {
+				let raw = std::fs::read_to_string(&s)
+					.map_err(|_| SvgError::Read(PathBuf::from(&s)))?;
+				opts.id_map = parse_id_map(&raw)
+					.map_err(|line| SvgError::CliValue("--id-map", format!("{s} (line {line})")))?;
+			},
+			Argument::KeyWithValue("--id-template", s) => { opts.id_template.replace(s); },
+			Argument::KeyWithValue("--keep-empty", s) => {
+				opts.keep_empty.extend(
+					s.split(',')
+						.map(str::trim)
+						.filter(|t| ! t.is_empty())
+						.map(str::to_ascii_lowercase)
+				);
+			},
+			Argument::KeyWithValue("--keep-root-attributes", s) => {
+				opts.keep_root_attributes.extend(
+					parse_root_attributes(&s)
+						.ok_or(SvgError::CliValue("--keep-root-attributes", s))?
+				);
+			},
 			Argument::KeyWithValue("-l" | "--list", s) => {
 				paths.read_paths_from_file(&s)
 					.map_err(|_| SvgError::Read(PathBuf::from(s)))?;
 			},
-			Argument::KeyWithValue("--map-class", s) => { class.replace(s); },
-			Argument::KeyWithValue("--map-id", s) => { id.replace(s); },
+			Argument::KeyWithValue("--log-level", s) => {
+				log::set_level(
+					LogLevel::from_name(&s).ok_or(SvgError::CliValue("--log-level", s))?
+				);
+			},
+			Argument::KeyWithValue("--max-depth", s) => {
+				opts.max_depth = s.trim().parse::<NonZeroUsize>()
+					.map_err(|_| SvgError::CliValue("--max-depth", s))?;
+			},
+			Argument::KeyWithValue("--max-id-length", s) => {
+				opts.max_id_length.replace(
+					s.trim().parse::<NonZeroUsize>()
+						.map_err(|_| SvgError::CliValue("--max-id-length", s))?
+				);
+			},
+			Argument::KeyWithValue("--max-total-bytes", s) => {
+				max_total_bytes.replace(
+					s.trim().parse::<NonZeroUsize>()
+						.map_err(|_| SvgError::CliValue("--max-total-bytes", s))?
+				);
+			},
+			Argument::KeyWithValue("--max-warnings", s) => {
+				max_warnings.replace(
+					s.trim().parse::<usize>()
+						.map_err(|_| SvgError::CliValue("--max-warnings", s))?
+				);
+			},
+			Argument::KeyWithValue("--map-class", s) => { opts.class.replace(s); },
+			Argument::KeyWithValue("--map-id", s) => { opts.id.replace(s); },
+			Argument::KeyWithValue("--mode", s) => {
+				opts.mode = SpriteMode::from_name(&s)
+					.ok_or(SvgError::CliValue("--mode", s))?;
+			},
+			Argument::KeyWithValue("--output-mode", s) => {
+				output_mode = OutputMode::from_name(&s)
+					.ok_or(SvgError::CliValue("--output-mode", s))?;
+			},
+			Argument::KeyWithValue("--output-style", s) => {
+				output_style = OutputStyle::from_name(&s)
+					.ok_or(SvgError::CliValue("--output-style", s))?;
+			},
+			Argument::KeyWithValue("--parse-timeout", s) => {
+				let ms = s.trim().parse::<NonZeroU64>()
+					.map_err(|_| SvgError::CliValue("--parse-timeout", s))?;
+				opts.parse_timeout.replace(Duration::from_millis(ms.get()));
+			},
 			Argument::KeyWithValue("-o" | "--output", s) => {
 				let s = PathBuf::from(s);
 				if ! s.is_dir() { out.replace(s); }
 			},
-			Argument::KeyWithValue("-p" | "--prefix", s) => { prefix = Cow::Owned(s); },
+			Argument::KeyWithValue("-p" | "--prefix", s) => { prefix.replace(s); },
+			Argument::KeyWithValue("--precision", s) => {
+				opts.precision.replace(
+					s.trim().parse::<u8>()
+						.map_err(|_| SvgError::CliValue("--precision", s))?
+				);
+			},
+			Argument::KeyWithValue("--report", s) => { report.replace(PathBuf::from(s)); },
+			Argument::KeyWithValue("--self-closing", s) => {
+				opts.self_closing = SelfClosing::from_name(&s)
+					.ok_or(SvgError::CliValue("--self-closing", s))?;
+			},
+			Argument::KeyWithValue("--stdin", s) => { stdin.replace(s); },
+			Argument::KeyWithValue("--symbol-class", s) => {
+				opts.symbol_class.replace(
+					parse_symbol_class(&s)
+						.ok_or(SvgError::CliValue("--symbol-class", s))?
+				);
+			},
+			Argument::KeyWithValue("--usage-href-style", s) => {
+				href_style = HrefStyle::from_name(&s)
+					.ok_or(SvgError::CliValue("--usage-href-style", s))?;
+			},
+			Argument::KeyWithValue("--used-ids", s) => {
+				let raw = std::fs::read_to_string(&s)
+					.map_err(|_| SvgError::Read(PathBuf::from(s)))?;
+				used_ids.replace(report::parse_used_ids(&raw));
+			},
+			Argument::KeyWithValue("--sort", s) => {
+				opts.sort = SortMode::from_name(&s)
+					.ok_or(SvgError::CliValue("--sort", s))?;
+			},
+			Argument::KeyWithValue("--usage-data", s) => {
+				let raw = std::fs::read_to_string(&s)
+					.map_err(|_| SvgError::Read(PathBuf::from(&s)))?;
+				opts.usage = parse_usage_data(&raw)
+					.map_err(|line| SvgError::CliValue("--usage-data", format!("{s} (line {line})")))?;
+			},
+			Argument::KeyWithValue("--viewbox-source", s) => {
+				opts.viewbox_source = ViewboxSource::from_name(&s)
+					.ok_or(SvgError::CliValue("--viewbox-source", s))?;
+			},
 
-			// Assume these are paths.
-			Argument::Other(s) => { paths = paths.with_path(s); },
+			// A lone dash means STDIN; anything else is assumed to be a
+			// path.
+			Argument::Other(s) => {
+				if s == "-" { stdin.get_or_insert_with(String::new); }
+				else if let Some(found) = glob_paths(&s)? {
+					log::debug(format_args!("Expanded {} path{}.", found.len(), if found.len() == 1 { "" } else { "s" }));
+					paths = paths.with_paths(found);
+				}
+				else { paths = paths.with_path(s); }
+			},
 			Argument::InvalidUtf8(s) => { paths = paths.with_path(s); },
 
 			// Nothing else is relevant.
@@ -137,36 +480,639 @@ fn main__() -> Result<(), SvgError> {
 		}
 	}
 
+	// Scripts are never fatal when explicitly allowed.
+	if allow_scripts { opts.error_on = opts.error_on.without(ContentWarnings::SCRIPTS); }
+
+	// Usage sorting needs usage data.
+	if matches!(opts.sort, SortMode::Usage) && opts.usage.is_empty() {
+		return Err(SvgError::CliRequires("--sort usage", "--usage-data"));
+	}
+
+	// Locked IDs need an owner.
+	if id_lock.is_some() && out.is_none() {
+		return Err(SvgError::CliRequires("--id-lock", "-o/--output"));
+	}
+
+	// Make sure the output path looks like an output path.
+	if let Some(path) = out.take() { out.replace(parse_output(path, opts.html_fragment)?); }
+
+	// Sort out the prefix. An explicit value takes priority, followed by the
+	// output-derived one, if requested. Or none at all.
+	if no_prefix {
+		if prefix.is_some() { return Err(SvgError::CliConflict("--no-prefix", "--prefix")); }
+		if prefix_from_output {
+			return Err(SvgError::CliConflict("--no-prefix", "--prefix-from-output"));
+		}
+		opts.prefix.clear();
+	}
+	else if let Some(prefix) = prefix { opts.prefix = prefix; }
+	else if prefix_from_output {
+		if let Some(path) = out.as_deref() {
+			opts.prefix = parse_output_prefix(path, opts.relaxed_ids)
+				.ok_or_else(|| SvgError::CliValue(
+					"--prefix-from-output",
+					path.to_string_lossy().into_owned(),
+				))?
+				.into_owned();
+		}
+	}
+
+	// Element names make for confusing prefixes.
+	if is_reserved_prefix(&opts.prefix) {
+		Msg::warning(format!(
+			"The prefix {:?} is also an SVG element name, which may prove confusing in selectors; consider something more distinctive.",
+			opts.prefix,
+		)).eprint();
+	}
+
 	// Find the files!
-	let map = Map::new(
-		id.as_deref(),
-		class.as_deref(),
-		hide,
-		&prefix,
-		&paths.into_vec_filtered(|p| Some(E_SVG) == Extension::try_from3(p))
-	)?;
-
-	// Save it to a file.
-	if let Some(path) = out {
-		write_atomic::write_file(&path, map.to_string().as_bytes())
+	let now = Instant::now();
+	let mut paths = paths.into_vec_filtered(|p| Some(E_SVG) == Extension::try_from3(p));
+	if ! exclude.is_empty() {
+		let before = paths.len();
+		paths.retain(|p| ! exclude.iter().any(|e| e.matches_path(p)));
+		log::debug(format_args!(
+			"Excluded {}.",
+			(before - paths.len()).nice_inflect("SVG file", "SVG files"),
+		));
+	}
+	if let Some(t) = timings.as_mut() { t.crawl = now.elapsed(); }
+	log::debug(format_args!("Found {}.", paths.len().nice_inflect("SVG file", "SVG files")));
+
+	// Read an image from STDIN? Without an explicit ID, one is built from
+	// the prefix.
+	let stdin = match stdin {
+		Some(id) => {
+			let id =
+				if id.is_empty() { opts.symbol_id("stdin").unwrap_or_default() }
+				else {
+					parse_stdin_id(&id, opts.relaxed_ids)
+						.ok_or(SvgError::CliValue("--stdin", id))?
+				};
+			let raw = std::io::read_to_string(std::io::stdin())
+				.map_err(|_| SvgError::Read(PathBuf::from("-")))?;
+			vec![(id, raw)]
+		},
+		None => Vec::new(),
+	};
+
+	// Just dump the parser's view of them?
+	if dump_ast {
+		if paths.is_empty() { return Err(SvgError::NoSvgs); }
+		for path in paths {
+			let raw = std::fs::read_to_string(&path)
+				.map_err(|_| SvgError::Read(path.clone()))?;
+			eprintln!("\x1b[1;95m{}\x1b[0m\n{}", path.display(), dump_events(&raw));
+		}
+		return Ok(());
+	}
+
+	let sources: Vec<(&str, &str)> = stdin.iter()
+		.map(|(id, raw)| (id.as_str(), raw.as_str()))
+		.collect();
+	let map = Map::with_sources(&opts, &paths, &sources)?;
+
+	// List the sources?
+	if report_paths { report::print_paths(&map); }
+
+	// Break them down by directory?
+	if report_by_dir { report::print_dir_counts(&map); }
+
+	// Mention any viewBox rounding.
+	if opts.integer_viewbox { report::print_rounded_viewboxes(&map); }
+
+	// Check for content-free symbols?
+	if report_empty { report::print_empty(&map); }
+
+	// Check for size inconsistencies?
+	if warn_odd_sizes { report::print_odd_sizes(&map); }
+
+	// Check for invisible shapes?
+	if opts.warn_zero_shapes { report::print_zero_shapes(&map); }
+
+	// Check for pixel-sized sources?
+	if opts.warn_fixed_dimensions { report::print_fixed_dimensions(&map); }
+
+	// Check the titles?
+	if opts.warn_title_mismatch { report::print_title_mismatches(&map); }
+
+	// List the colors?
+	if opts.report_colors { report::print_colors(&map); }
+
+	// List the fonts?
+	if opts.report_fonts { report::print_fonts(&map); }
+
+	// List the strokes?
+	if opts.report_strokes { report::print_strokes(&map); }
+
+	// List the control characters?
+	if opts.report_control_chars { report::print_control_chars(&map); }
+
+	// Check for leftover namespaces?
+	if opts.report_unused_namespaces { report::print_unused_namespaces(&map); }
+
+	// List the derived viewBoxes?
+	if report_derived_viewbox { report::print_derived_viewboxes(&map); }
+
+	// List the unused symbols?
+	if let Some(used) = used_ids.as_ref() { report::print_unreferenced(&map, used); }
+
+	// Too many warnings?
+	if let Some(max) = max_warnings {
+		let count = report::warning_count(&map);
+		if max < count { return Err(SvgError::TooManyWarnings(count, max)); }
+	}
+
+	let now = Instant::now();
+	let svg = map.render_within(output_style, max_total_bytes)?;
+	if let Some(t) = timings.as_mut() { t.serialize = now.elapsed(); }
+
+	// Make sure the IDs aren't claimed by some other sprite.
+	let id_lock = match id_lock {
+		Some(path) => {
+			let raw = match std::fs::read_to_string(&path) {
+				Ok(raw) => raw,
+				Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+				Err(_) => return Err(SvgError::Read(path)),
+			};
+			let owner = out.as_deref().map_or_else(String::new, lock_owner);
+			let lock = report::id_lock(&map, &owner, &raw)
+				.map_err(|list| SvgError::IdClaimed(path.clone(), list))?;
+			Some((path, lock))
+		},
+		None => None,
+	};
+
+	// Save a report?
+	if let Some(path) = report.filter(|_| ! dry_run) {
+		write_atomic::write_file(&path, report::json_report(&map, svg.len()).as_bytes())
 			.map_err(|_| SvgError::Write)?;
+	}
 
+	// Save the images individually too?
+	if let Some(dir) = also_individual.filter(|_| ! dry_run) {
+		let len = write_individual(&dir, &map)?;
 		Msg::success(format!(
-			"A sprite with {} has been saved to {:?}",
+			"{} been saved to {:?}",
+			len.nice_inflect("individual image has", "individual images have"),
+			std::fs::canonicalize(&dir).unwrap_or(dir),
+		)).eprint();
+	}
+
+	// Save a stylesheet?
+	if let Some(path) = css.filter(|_| ! dry_run) {
+		write_atomic::write_file(&path, report::css(&map).as_bytes())
+			.map_err(|_| SvgError::Write)?;
+	}
+
+	// Save a demo page?
+	if let Some(path) = demo.filter(|_| ! dry_run) {
+		write_atomic::write_file(&path, report::demo(&map, href_style).as_bytes())
+			.map_err(|_| SvgError::Write)?;
+	}
+
+	// Just a preview?
+	if dry_run {
+		if verbose { eprint!("{}", report::preview(&map)); }
+
+		Msg::info(format!(
+			"A sprite with {} ({} bytes) would have been generated.",
 			map.len().nice_inflect("image", "images"),
-			std::fs::canonicalize(&path).unwrap()
+			svg.len(),
 		)).eprint();
 	}
+	// Save it to a file, compressed and/or not.
+	else if let Some(path) = out {
+		let (plain, gz) =
+			if ! gzip { (Some(path), None) }
+			else if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("gz")) {
+				(None, Some(path))
+			}
+			else {
+				let mut gz = path.clone().into_os_string();
+				gz.push(".gz");
+				(Some(path), Some(PathBuf::from(gz)))
+			};
+
+		// Compress first so a failure leaves everything as it was.
+		let gz = match gz {
+			Some(path) => Some((gzip_encode(svg.as_bytes())?, path)),
+			None => None,
+		};
+
+		if let Some(path) = plain {
+			if if_changed && unchanged(&path, svg.as_bytes()) {
+				Msg::info(format!(
+					"No changes; {:?} was left as-is.",
+					std::fs::canonicalize(&path).unwrap_or(path),
+				)).eprint();
+			}
+			else {
+				output_mode.write(&path, svg.as_bytes())?;
+
+				Msg::success(format!(
+					"A sprite with {} has been saved to {:?} \x1b[2m({})\x1b[0m",
+					map.len().nice_inflect("image", "images"),
+					std::fs::canonicalize(&path).unwrap_or(path),
+					report::savings(&map, svg.len()),
+				)).eprint();
+			}
+		}
+
+		if let Some((data, path)) = gz {
+			if if_changed && unchanged(&path, &data) {
+				Msg::info(format!(
+					"No changes; {:?} was left as-is.",
+					std::fs::canonicalize(&path).unwrap_or(path),
+				)).eprint();
+			}
+			else {
+				output_mode.write(&path, &data)?;
+
+				Msg::success(format!(
+					"A gzipped sprite with {} has been saved to {:?} \x1b[2m({})\x1b[0m",
+					map.len().nice_inflect("image", "images"),
+					std::fs::canonicalize(&path).unwrap_or(path),
+					report::savings(&map, data.len()),
+				)).eprint();
+			}
+		}
+	}
 	// Just print it.
 	else {
+		if gzip {
+			Msg::warning("Compression requires an -o/--output path; printing the sprite as-is.")
+				.eprint();
+		}
+
 		Msg::success(format!(
-			"Generated a sprite with {}.",
+			"Generated a sprite with {}. \x1b[2m({})\x1b[0m",
 			map.len().nice_inflect("image", "images"),
+			report::savings(&map, svg.len()),
 		)).eprint();
 
-		println!("{map}");
+		println!("{svg}");
+	}
+
+	// Update the lock.
+	if let Some((path, lock)) = id_lock.filter(|_| ! dry_run) {
+		write_atomic::write_file(&path, lock.as_bytes())
+			.map_err(|_| SvgError::Write)?;
+	}
+
+	// Compare it against another map?
+	if let Some(path) = compare {
+		let raw = std::fs::read_to_string(&path)
+			.map_err(|_| SvgError::Read(path.clone()))?;
+		let old = sprite_symbols(&raw).ok_or_else(|| SvgError::Parse(path.clone()))?;
+		let new = sprite_symbols(&svg).unwrap_or_default();
+		let diff = report::print_sprite_diff(&old, &new, &path);
+		if fail_on_diff && diff != 0 { return Err(SvgError::SpriteDiff(diff)); }
+	}
+
+	// How long did all that take?
+	if let Some(t) = timings {
+		Msg::notice("Timing:").eprint();
+		eprint!("{}", t.report(&map));
 	}
 
 	// Done!
 	Ok(())
 }
+
+/// # Write Individual Images.
+///
+/// Save each symbol as a standalone SVG — `ID.svg` — in `dir`, creating it
+/// if necessary, and return the number written.
+fn write_individual(dir: &Path, map: &Map) -> Result<usize, SvgError> {
+	std::fs::create_dir_all(dir).map_err(|_| SvgError::Write)?;
+	let images = map.individual();
+	for (id, svg) in &images {
+		write_atomic::write_file(dir.join(format!("{id}.svg")), svg.as_bytes())
+			.map_err(|_| SvgError::Write)?;
+	}
+	Ok(images.len())
+}
+
+/// # Glob Paths.
+///
+/// If `src` looks like a glob pattern — and isn't simply a path that happens
+/// to contain wildcard characters — return its matches, if any.
+///
+/// ## Errors
+///
+/// An error is returned if the pattern is invalid.
+fn glob_paths(src: &str) -> Result<Option<Vec<PathBuf>>, SvgError> {
+	if ! src.contains(['*', '?', '[']) || Path::new(src).exists() { return Ok(None); }
+	glob::glob(src)
+		.map(|found| Some(found.filter_map(Result::ok).collect()))
+		.map_err(|_| SvgError::CliValue("<PATH(S)>", src.to_owned()))
+}
+
+/// # Parse Output Path.
+///
+/// Make sure the output path ends with `.svg` — or `.svg.gz`, or for HTML
+/// fragments, `.html` — appending `.svg` if it has no extension at all, so a
+/// mistyped flag can't clobber some other kind of file.
+///
+/// ## Errors
+///
+/// An error is returned if the path has any other extension.
+fn parse_output(path: PathBuf, html: bool) -> Result<PathBuf, SvgError> {
+	let Some(ext) = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase) else {
+		if path.extension().is_some() { return Err(SvgError::InvalidDst(path)); }
+		let mut path = path.into_os_string();
+		path.push(".svg");
+		return Ok(PathBuf::from(path));
+	};
+
+	let valid = match ext.as_str() {
+		"svg" => true,
+		"gz" => path.file_stem()
+			.and_then(|s| Path::new(s).extension())
+			.is_some_and(|e| e.eq_ignore_ascii_case("svg")),
+		"htm" | "html" => html,
+		_ => false,
+	};
+
+	if valid { Ok(path) }
+	else { Err(SvgError::InvalidDst(path)) }
+}
+
+/// # Lock Owner.
+///
+/// Return the canonical form of the output path so the same file is always
+/// recorded the same way in the ID lock, however it was spelled on the
+/// command line. Files that don't exist yet are resolved via their parent
+/// directory, falling back to a plain absolute path.
+fn lock_owner(path: &Path) -> String {
+	std::fs::canonicalize(path)
+		.or_else(|_| {
+			let name = path.file_name().ok_or(ErrorKind::NotFound)?;
+			let dir = path.parent().filter(|p| ! p.as_os_str().is_empty())
+				.unwrap_or_else(|| Path::new("."));
+			std::fs::canonicalize(dir).map(|dir| dir.join(name))
+		})
+		.or_else(|_| std::path::absolute(path))
+		.unwrap_or_else(|_| path.to_path_buf())
+		.to_string_lossy()
+		.into_owned()
+}
+
+/// # Parse Exclusion Pattern.
+///
+/// Patterns with wildcards are treated as globs; anything else is matched as
+/// a substring. Either way, the match is made against the full path.
+///
+/// Returns `None` if the pattern is empty or invalid.
+fn parse_exclude(src: &str) -> Option<glob::Pattern> {
+	if src.is_empty() { None }
+	else if src.contains(['*', '?', '[']) { glob::Pattern::new(src).ok() }
+	else { glob::Pattern::new(&format!("*{}*", glob::Pattern::escape(src))).ok() }
+}
+
+/// # Unchanged?
+///
+/// Returns `true` if the file at `path` already holds exactly `data`.
+fn unchanged(path: &Path, data: &[u8]) -> bool {
+	std::fs::read(path).is_ok_and(|old| old == data)
+}
+
+/// # Gzip.
+///
+/// Compress the data, gzip-style, at the highest level.
+fn gzip_encode(src: &[u8]) -> Result<Vec<u8>, SvgError> {
+	let mut enc = Compressor::new(CompressionLvl::best());
+	let mut out = vec![0; enc.gzip_compress_bound(src.len())];
+	let len = enc.gzip_compress(src, &mut out).map_err(|_| SvgError::Write)?;
+	out.truncate(len);
+	Ok(out)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use libdeflater::Decompressor;
+
+	#[test]
+	fn test_gzip_encode() {
+		let raw = r#"<svg aria-hidden="true" xmlns="http://www.w3.org/2000/svg"><symbol id="i-dot" viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></symbol></svg>"#;
+		let gz = gzip_encode(raw.as_bytes()).expect("Compression failed.");
+		assert!(gz.starts_with(&[0x1f, 0x8b]), "Missing gzip magic.");
+
+		let mut out = vec![0; raw.len()];
+		let len = Decompressor::new().gzip_decompress(&gz, &mut out)
+			.expect("Decompression failed.");
+		assert_eq!(&out[..len], raw.as_bytes());
+	}
+
+	#[test]
+	fn test_write_individual() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let dir = std::env::temp_dir().join("yesvgmap-tests/individual");
+		assert!(matches!(write_individual(&dir, &map), Ok(2)));
+
+		// Each file should match its symbol in the sprite.
+		let sprite = sprite_symbols(&map.to_string()).expect("Unable to read sprite.");
+		assert_eq!(sprite.len(), 2);
+		for (id, symbol) in sprite {
+			let raw = std::fs::read_to_string(dir.join(format!("{id}.svg")))
+				.expect("Missing individual image.");
+			assert!(raw.starts_with(r#"<svg viewBox=""#), "Unexpected image: {raw}");
+			assert!(raw.contains(r#" xmlns="http://www.w3.org/2000/svg">"#), "Unexpected image: {raw}");
+			assert!(! raw.contains(&id), "The ID should not be carried over.");
+
+			// Swap the wrappers and they should be identical.
+			let inner = raw.split_once('>').map(|(_, rest)| rest.trim_end_matches("</svg>"))
+				.expect("Malformed image.");
+			let viewbox = raw.split('"').nth(1).expect("Missing viewBox.");
+			assert_eq!(
+				symbol,
+				format!(r#"<symbol id="{id}" viewBox="{viewbox}">{inner}</symbol>"#),
+			);
+		}
+	}
+
+	#[test]
+	fn test_timings() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		assert_eq!(map.parse_times().len(), 2);
+
+		let summary = Timings::new().report(&map);
+		for label in ["crawl", "parse", "serialize", "slowest", "total"] {
+			assert!(
+				summary.lines().any(|l| l.trim_start().starts_with(label)),
+				"Missing {label}: {summary}",
+			);
+		}
+		assert!(summary.contains("(2 sources)"));
+		assert!(summary.contains("test-assets/"));
+	}
+
+	#[test]
+	fn test_glob_paths() {
+		// Plain paths are left alone.
+		assert!(matches!(glob_paths("test-assets"), Ok(None)));
+		assert!(matches!(glob_paths("test-assets/close.svg"), Ok(None)));
+
+		// Globs are expanded.
+		let found = glob_paths("test-assets/arrow-*.svg")
+			.expect("Invalid pattern.")
+			.expect("Not a glob.");
+		assert_eq!(
+			found,
+			[
+				PathBuf::from("test-assets/arrow-1.svg"),
+				PathBuf::from("test-assets/arrow-2.svg"),
+				PathBuf::from("test-assets/arrow-3.svg"),
+			],
+		);
+		let found = glob_paths("test-assets/**/*.svg")
+			.expect("Invalid pattern.")
+			.expect("Not a glob.");
+		assert!(found.contains(&PathBuf::from("test-assets/close.svg")));
+
+		// Even if there's nothing to find, which leaves the map with no SVGs.
+		let found = glob_paths("test-assets/nope-*.svg")
+			.expect("Invalid pattern.")
+			.expect("Not a glob.");
+		assert!(found.is_empty());
+		assert!(matches!(
+			Map::with_sources(&MapOptions::default(), &found, &[]),
+			Err(SvgError::NoSvgs),
+		));
+
+		// Bad patterns are bad.
+		assert!(matches!(glob_paths("test-assets/***.svg"), Err(SvgError::CliValue(_, _))));
+	}
+
+	#[test]
+	fn test_exclude() {
+		let exclude = [
+			parse_exclude("/_unused/").expect("Invalid pattern."),
+			parse_exclude("**/*.min.svg").expect("Invalid pattern."),
+			parse_exclude("*/v[0-9]/*").expect("Invalid pattern."),
+		];
+		for (path, expected) in [
+			("/icons/close.svg", false),
+			("/icons/_unused/close.svg", true),
+			("/icons/_unused.svg", false),
+			("/icons/close.min.svg", true),
+			("/icons/min.svg", false),
+			("/icons/v2/close.svg", true),
+			("/icons/vx/close.svg", false),
+		] {
+			assert_eq!(
+				exclude.iter().any(|e| e.matches_path(Path::new(path))),
+				expected,
+				"Wrong answer for {path}.",
+			);
+		}
+
+		assert!(parse_exclude("").is_none());
+		assert!(parse_exclude("***.svg").is_none());
+	}
+
+	#[test]
+	fn test_lock_owner() {
+		// The same file spelled different ways should have the same owner,
+		// whether or not it exists yet.
+		for (a, b) in [
+			("src/main.rs", "./src/../src/main.rs"),
+			("map.svg", "src/../map.svg"),
+			("./dist.svg", "dist.svg"),
+		] {
+			let owner = lock_owner(Path::new(a));
+			assert_eq!(owner, lock_owner(Path::new(b)), "Owner mismatch: {a} vs {b}.");
+			assert!(Path::new(&owner).is_absolute(), "Owner should be absolute: {owner}.");
+		}
+	}
+
+	#[test]
+	fn test_parse_output() {
+		for (raw, html, expected) in [
+			("map.svg", false, Some("map.svg")),
+			("dist/MAP.SVG", false, Some("dist/MAP.SVG")),
+			("map.svg.gz", false, Some("map.svg.gz")),
+			("map", false, Some("map.svg")),
+			("dist/map", true, Some("dist/map.svg")),
+			("map.html", true, Some("map.html")),
+			("map.htm", true, Some("map.htm")),
+			("map.html", false, None),
+			("bundle.js", false, None),
+			("bundle.js", true, None),
+			("map.gz", false, None),
+			("map.js.gz", false, None),
+		] {
+			let res = parse_output(PathBuf::from(raw), html);
+			match expected {
+				Some(expected) => assert_eq!(
+					res.ok(),
+					Some(PathBuf::from(expected)),
+					"Wrong answer for {raw}.",
+				),
+				None => assert!(
+					matches!(res, Err(SvgError::InvalidDst(p)) if p == Path::new(raw)),
+					"Wrong answer for {raw}.",
+				),
+			}
+		}
+
+		// The error should mention all of the options.
+		let err = parse_output(PathBuf::from("map.png"), false).err().map(|e| e.to_string());
+		assert!(
+			err.as_deref().is_some_and(|e| [".svg ", ".svg.gz", ".html", ".htm "].iter().all(|x| e.contains(x))),
+			"Misleading error: {err:?}",
+		);
+	}
+
+	#[test]
+	fn test_unchanged() {
+		let dir = std::env::temp_dir().join("yesvgmap-tests");
+		std::fs::create_dir_all(&dir).expect("Unable to create temporary directory.");
+		let path = dir.join("unchanged.svg");
+		let _res = std::fs::remove_file(&path);
+
+		// Nothing to compare against.
+		assert!(! unchanged(&path, b"<svg/>"));
+
+		std::fs::write(&path, b"<svg/>").expect("Unable to write file.");
+		assert!(unchanged(&path, b"<svg/>"));
+		assert!(! unchanged(&path, b"<svg></svg>"));
+		assert!(! unchanged(&path, b""));
+	}
+
+	#[test]
+	fn test_output_mode() {
+		use std::os::unix::fs::MetadataExt;
+
+		assert_eq!(OutputMode::from_name("atomic"), Some(OutputMode::Atomic));
+		assert_eq!(OutputMode::from_name("truncate"), Some(OutputMode::Truncate));
+		assert_eq!(OutputMode::from_name("append"), None);
+
+		let dir = std::env::temp_dir().join("yesvgmap-tests");
+		std::fs::create_dir_all(&dir).expect("Unable to create temporary directory.");
+		let path = dir.join("output-mode.svg");
+		std::fs::write(&path, "a much longer placeholder than the map").expect("Unable to write file.");
+		let ino = std::fs::metadata(&path).expect("Missing file.").ino();
+
+		// Truncation should reuse the same file.
+		OutputMode::Truncate.write(&path, b"<svg/>").expect("Write failed.");
+		assert_eq!(std::fs::read(&path).expect("Unable to read file."), b"<svg/>");
+		assert_eq!(std::fs::metadata(&path).expect("Missing file.").ino(), ino);
+
+		// Atomic writes should still work too.
+		OutputMode::Atomic.write(&path, b"<svg></svg>").expect("Write failed.");
+		assert_eq!(std::fs::read(&path).expect("Unable to read file."), b"<svg></svg>");
+	}
+}
@@ -0,0 +1,359 @@
+/*!
+# Yesvgmap: Options
+*/
+
+use crate::{
+	ContentWarnings,
+	img::{
+		HideType,
+		SelfClosing,
+		SortMode,
+		SpriteMode,
+		valid_id_start,
+		ViewboxSource,
+	},
+};
+use std::{
+	collections::BTreeMap,
+	num::NonZeroUsize,
+	time::Duration,
+};
+
+
+
+/// # Default Maximum Nesting Depth.
+const MAX_DEPTH: NonZeroUsize = NonZeroUsize::new(256).unwrap();
+
+
+
+#[derive(Debug, Clone)]
+#[expect(clippy::struct_excessive_bools, reason = "These are independent CLI switches.")]
+/// # Map Options.
+///
+/// This holds the various CLI-derived settings that affect how the map is
+/// built.
+pub(super) struct MapOptions {
+	/// # Map ID.
+	pub(super) id: Option<String>,
+
+	/// # Map Class.
+	pub(super) class: Option<String>,
+
+	/// # Hide Type.
+	pub(super) hide: HideType,
+
+	/// # Symbol ID Prefix.
+	///
+	/// If empty, IDs are just the (sanitized) stems.
+	pub(super) prefix: String,
+
+	/// # Attribute Order.
+	///
+	/// These attributes are serialized first, in this order, ahead of the
+	/// (alphabetical) rest.
+	pub(super) attribute_order: Vec<String>,
+
+	/// # Check Well-Formedness.
+	///
+	/// When `true`, sources are held to the XML well-formedness rules the
+	/// parser would otherwise overlook.
+	pub(super) check_well_formed: bool,
+
+	/// # Clean Attribute Whitespace.
+	///
+	/// When `true`, whitespace runs within attribute values are collapsed to
+	/// single spaces.
+	pub(super) clean_attr_whitespace: bool,
+
+	/// # Doctype.
+	///
+	/// When `true`, standalone maps are preceded by the SVG 1.1 `DOCTYPE`.
+	pub(super) doctype: bool,
+
+	/// # Emit Dimensions.
+	///
+	/// When `true`, each symbol will be given `data-width` and `data-height`
+	/// attributes matching its `viewBox`.
+	pub(super) emit_dimensions: bool,
+
+	/// # Include Error Context.
+	///
+	/// When `true`, parse errors will include a snippet of the source near
+	/// the point of failure.
+	pub(super) error_context: bool,
+
+	/// # Warnings to Treat as Errors.
+	pub(super) error_on: ContentWarnings,
+
+	/// # ID Map.
+	///
+	/// Source file stems mapped to the ID stems to use in their place.
+	pub(super) id_map: BTreeMap<String, String>,
+
+	/// # ID Template.
+	///
+	/// If present, symbol IDs are built from this template rather than the
+	/// usual `PREFIX-STEM`.
+	pub(super) id_template: Option<String>,
+
+	/// # HTML Fragment.
+	///
+	/// When `true`, the map is wrapped in a hidden `<div>` for inline HTML
+	/// use.
+	pub(super) html_fragment: bool,
+
+	/// # Inherit Root Presentation.
+	///
+	/// When `true`, presentation attributes like `fill` found on the source
+	/// root `<svg>` are applied to a group wrapping the symbol's content.
+	pub(super) inherit_root_presentation: bool,
+
+	/// # Integer `viewBox`.
+	///
+	/// When `true`, fractional `viewBox` widths and heights are rounded to
+	/// whole numbers.
+	pub(super) integer_viewbox: bool,
+
+	/// # Keep Empty Tags.
+	///
+	/// Elements with these tag names will be kept even if they have no
+	/// attributes or children.
+	pub(super) keep_empty: Vec<String>,
+
+	/// # Keep Data Attributes.
+	///
+	/// When `true`, any `data-*` attributes on the source's root `<svg>` will
+	/// be copied over to the `<symbol>`.
+	pub(super) keep_data: bool,
+
+	/// # Keep Root Attributes.
+	///
+	/// These attributes, if present on the source's root `<svg>`, will be
+	/// copied over to the `<symbol>`.
+	pub(super) keep_root_attributes: Vec<String>,
+
+	/// # Maximum Nesting Depth.
+	///
+	/// Sources with elements nested more deeply than this are rejected.
+	pub(super) max_depth: NonZeroUsize,
+
+	/// # Maximum Symbol ID Length.
+	pub(super) max_id_length: Option<NonZeroUsize>,
+
+	/// # Minify Path Data.
+	///
+	/// When `true`, `d` and `points` values are rewritten as compactly as
+	/// possible without changing any of the numbers.
+	pub(super) minify: bool,
+
+	/// # Sprite Mode.
+	pub(super) mode: SpriteMode,
+
+	/// # Monochrome.
+	///
+	/// When `true`, concrete `fill` and `stroke` colors are replaced with
+	/// `currentColor`.
+	pub(super) mono: bool,
+
+	/// # Prefix Inner IDs.
+	///
+	/// When `true`, inner element IDs — and references to them — are rewritten
+	/// to begin with the symbol's own ID.
+	pub(super) normalize_ids_to_prefix: bool,
+
+	/// # Parse Timeout.
+	///
+	/// If present, parsing a single source will be abandoned if it takes
+	/// longer than this.
+	pub(super) parse_timeout: Option<Duration>,
+
+	/// # `viewBox` Precision.
+	///
+	/// When set, `viewBox` widths and heights are rounded to this many
+	/// decimal places.
+	pub(super) precision: Option<u8>,
+
+	/// # Read ID Comments.
+	///
+	/// When `true`, a `<!-- yesvgmap-id: … -->` comment ahead of a source's
+	/// root element takes the place of its file stem.
+	pub(super) read_id_comments: bool,
+
+	/// # Relaxed IDs.
+	///
+	/// When `true`, generated IDs (and output-derived prefixes) may begin
+	/// with a digit or underscore rather than only a letter.
+	pub(super) relaxed_ids: bool,
+
+	/// # Report Colors.
+	///
+	/// When `true`, color-valued attributes and style declarations are
+	/// collected for reporting.
+	pub(super) report_colors: bool,
+
+	/// # Report Control Characters.
+	///
+	/// When `true`, attribute values and text are checked for stray control
+	/// characters.
+	pub(super) report_control_chars: bool,
+
+	/// # Report Fonts.
+	///
+	/// When `true`, `font-family` attributes and style declarations are
+	/// collected for reporting.
+	pub(super) report_fonts: bool,
+
+	/// # Report Strokes.
+	///
+	/// When `true`, each symbol is checked for stroked elements lacking a
+	/// `vector-effect`.
+	pub(super) report_strokes: bool,
+
+	/// # Report Unused Namespaces.
+	///
+	/// When `true`, each symbol is checked for `xmlns:*` declarations that
+	/// nothing within it references.
+	pub(super) report_unused_namespaces: bool,
+
+	/// # Self-Closing Style.
+	pub(super) self_closing: SelfClosing,
+
+	/// # Symbol Sort Order.
+	pub(super) sort: SortMode,
+
+	/// # Split Multi-Icon Sources.
+	///
+	/// When `true`, sources with multiple top-level `<symbol>` or `<g id>`
+	/// elements are split into separate symbols.
+	pub(super) split_multi: bool,
+
+	/// # Strict.
+	///
+	/// When `true`, all content warnings are treated as errors, as are the
+	/// findings of any enabled control character, zero-dimension shape, or
+	/// integer `viewBox` checks.
+	pub(super) strict: bool,
+
+	/// # Strip Version Attributes.
+	///
+	/// When `true`, `version` and `baseProfile` attributes are removed from
+	/// the symbols and their children.
+	pub(super) strip_version: bool,
+
+	/// # Symbol Class.
+	///
+	/// A class to add to every symbol.
+	pub(super) symbol_class: Option<String>,
+
+	/// # Themable Colors.
+	///
+	/// When `true`, the colors of multi-color symbols are swapped for CSS
+	/// custom properties.
+	pub(super) themable: bool,
+
+	/// # Trim Path Whitespace.
+	///
+	/// When `true`, whitespace in `d` and `points` values is collapsed, and
+	/// removed from around commas.
+	pub(super) trim_path_whitespace: bool,
+
+	/// # Usage Counts.
+	///
+	/// Symbol IDs and how often they're used, for [`SortMode::Usage`].
+	pub(super) usage: BTreeMap<String, u64>,
+
+	/// # `viewBox` Source.
+	pub(super) viewbox_source: ViewboxSource,
+
+	/// # Warn About Fixed Dimensions.
+	///
+	/// When `true`, each source root is checked for absolute `width`/`height`
+	/// units alongside a `viewBox`.
+	pub(super) warn_fixed_dimensions: bool,
+
+	/// # Warn About Mismatched Titles.
+	///
+	/// When `true`, each symbol's `<title>` (if any) is compared against its
+	/// source file name.
+	pub(super) warn_title_mismatch: bool,
+
+	/// # Summarize Warnings.
+	///
+	/// When `true`, content warnings are aggregated by category rather than
+	/// listed per file.
+	pub(super) warnings_summary: bool,
+
+	/// # Warn About Zero-Dimension Shapes.
+	///
+	/// When `true`, shapes with zero-valued widths, heights, or radii are
+	/// looked for.
+	pub(super) warn_zero_shapes: bool,
+}
+
+impl Default for MapOptions {
+	fn default() -> Self {
+		Self {
+			id: None,
+			class: None,
+			hide: HideType::None,
+			prefix: String::from("i"),
+			attribute_order: Vec::new(),
+			check_well_formed: false,
+			clean_attr_whitespace: false,
+			doctype: false,
+			emit_dimensions: false,
+			error_context: false,
+			error_on: ContentWarnings::NONE,
+			id_map: BTreeMap::new(),
+			id_template: None,
+			html_fragment: false,
+			inherit_root_presentation: false,
+			integer_viewbox: false,
+			keep_data: false,
+			keep_empty: Vec::new(),
+			keep_root_attributes: Vec::new(),
+			max_depth: MAX_DEPTH,
+			max_id_length: None,
+			minify: false,
+			mode: SpriteMode::Symbol,
+			mono: false,
+			normalize_ids_to_prefix: false,
+			parse_timeout: None,
+			precision: None,
+			read_id_comments: false,
+			relaxed_ids: false,
+			report_colors: false,
+			report_control_chars: false,
+			report_fonts: false,
+			report_strokes: false,
+			report_unused_namespaces: false,
+			self_closing: SelfClosing::Always,
+			sort: SortMode::Alpha,
+			split_multi: false,
+			strict: false,
+			strip_version: false,
+			symbol_class: None,
+			themable: false,
+			trim_path_whitespace: false,
+			usage: BTreeMap::new(),
+			viewbox_source: ViewboxSource::Auto,
+			warn_fixed_dimensions: false,
+			warn_title_mismatch: false,
+			warnings_summary: false,
+			warn_zero_shapes: false,
+		}
+	}
+}
+
+impl MapOptions {
+	/// # Symbol ID.
+	///
+	/// Return the ID for a (sanitized) stem — `PREFIX-STEM`, or just `STEM`
+	/// if the prefix is empty — or `None` if the latter wouldn't begin with a
+	/// letter (or under relaxed rules, a digit or `_`).
+	pub(super) fn symbol_id(&self, stem: &str) -> Option<String> {
+		if ! self.prefix.is_empty() { Some(format!("{}-{stem}", self.prefix)) }
+		else if valid_id_start(stem, self.relaxed_ids) { Some(stem.to_owned()) }
+		else { None }
+	}
+}
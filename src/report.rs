@@ -0,0 +1,1432 @@
+/*!
+# Yesvgmap: Reports
+*/
+
+use crate::{
+	ContentWarnings,
+	img::{
+		Map,
+		MapSymbol,
+	},
+};
+use dactyl::{
+	NicePercent,
+	traits::NiceInflection,
+};
+use fyi_msg::Msg;
+use std::{
+	collections::{
+		BTreeMap,
+		BTreeSet,
+	},
+	fmt::Write,
+	path::Path,
+	time::Duration,
+};
+
+
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # Usage `href` Style.
+///
+/// This determines how the `<use>` references in the generated demo page
+/// point to their symbols.
+pub(super) enum HrefStyle {
+	#[default]
+	/// # Plain `href`.
+	Href,
+
+	/// # Legacy `xlink:href`.
+	Xlink,
+
+	/// # Both.
+	///
+	/// For maximum compatibility.
+	Both,
+}
+
+impl HrefStyle {
+	/// # From Name.
+	///
+	/// Return the style corresponding to the (CLI) name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		match src.trim() {
+			"both" => Some(Self::Both),
+			"href" => Some(Self::Href),
+			"xlink" => Some(Self::Xlink),
+			_ => None,
+		}
+	}
+
+	/// # Attribute Names.
+	const fn attrs(self) -> &'static [&'static str] {
+		match self {
+			Self::Href => &["href"],
+			Self::Xlink => &["xlink:href"],
+			Self::Both => &["href", "xlink:href"],
+		}
+	}
+}
+
+
+
+/// # JSON Report.
+///
+/// Build a single JSON document describing the map's symbols, per-file
+/// warnings, and aggregate stats, in that order.
+///
+/// Symbols are listed in map order; warnings are listed by file name.
+pub(super) fn json_report(map: &Map, bytes: usize) -> String {
+	let mut out = String::with_capacity(256 + map.len() * 128);
+
+	// Symbols.
+	out.push_str(r#"{"symbols":["#);
+	for (idx, s) in map.symbols().iter().enumerate() {
+		if idx != 0 { out.push(','); }
+		out.push_str(r#"{"id":"#);
+		json_str(&mut out, s.id());
+		out.push_str(r#","source":"#);
+		json_str(&mut out, &s.src().to_string_lossy());
+		out.push_str(r#","viewBox":"#);
+		json_str(&mut out, s.viewbox());
+		if let Some((w, h)) = s.dimensions() {
+			let _res = write!(out, r#","width":{w},"height":{h}"#);
+		}
+		else { out.push_str(r#","width":null,"height":null"#); }
+		out.push_str(r#","warnings":["#);
+		json_list(&mut out, s.warnings().names());
+		out.push_str("]}");
+	}
+
+	// Warnings.
+	out.push_str(r#"],"warnings":["#);
+	for (idx, (p, w)) in map.warnings().iter().enumerate() {
+		if idx != 0 { out.push(','); }
+		out.push_str(r#"{"source":"#);
+		json_str(&mut out, &p.to_string_lossy());
+		out.push_str(r#","categories":["#);
+		json_list(&mut out, w.names());
+		out.push_str("]}");
+	}
+
+	// Stats.
+	let _res = write!(
+		out,
+		r#"],"stats":{{"symbols":{},"warned":{},"bytes":{bytes}}}}}"#,
+		map.len(),
+		map.warnings().len(),
+	);
+
+	out
+}
+
+/// # JSON Report Schema.
+///
+/// Return the JSON Schema describing the output of [`json_report`]. The
+/// warning categories are filled in from [`ContentWarnings`] so they can't
+/// drift.
+pub(super) fn json_report_schema() -> String {
+	let mut categories = String::new();
+	json_list(&mut categories, ContentWarnings::categories().map(|(_, name)| name));
+
+	format!(r##"{{
+	"$schema": "https://json-schema.org/draft/2020-12/schema",
+	"title": "Yesvgmap Report",
+	"type": "object",
+	"required": ["symbols", "warnings", "stats"],
+	"additionalProperties": false,
+	"properties": {{
+		"symbols": {{
+			"description": "The symbols, in map order.",
+			"type": "array",
+			"items": {{
+				"type": "object",
+				"required": ["id", "source", "viewBox", "width", "height", "warnings"],
+				"additionalProperties": false,
+				"properties": {{
+					"id": {{ "type": "string" }},
+					"source": {{ "type": "string" }},
+					"viewBox": {{ "type": "string" }},
+					"width": {{ "type": ["number", "null"] }},
+					"height": {{ "type": ["number", "null"] }},
+					"warnings": {{ "$ref": "#/$defs/categories" }}
+				}}
+			}}
+		}},
+		"warnings": {{
+			"description": "The files with content warnings, by file name.",
+			"type": "array",
+			"items": {{
+				"type": "object",
+				"required": ["source", "categories"],
+				"additionalProperties": false,
+				"properties": {{
+					"source": {{ "type": "string" }},
+					"categories": {{ "$ref": "#/$defs/categories" }}
+				}}
+			}}
+		}},
+		"stats": {{
+			"type": "object",
+			"required": ["symbols", "warned", "bytes"],
+			"additionalProperties": false,
+			"properties": {{
+				"symbols": {{ "type": "integer", "minimum": 0 }},
+				"warned": {{ "type": "integer", "minimum": 0 }},
+				"bytes": {{ "type": "integer", "minimum": 0 }}
+			}}
+		}}
+	}},
+	"$defs": {{
+		"categories": {{
+			"type": "array",
+			"uniqueItems": true,
+			"items": {{ "enum": [{categories}] }}
+		}}
+	}}
+}}
+"##)
+}
+
+/// # Demo Page.
+///
+/// Build a standalone HTML document with the map inlined, followed by a
+/// labeled `<use>` reference for each symbol, for visually confirming
+/// everything renders as expected.
+pub(super) fn demo(map: &Map, href: HrefStyle) -> String {
+	let mut out = String::from(r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+	<meta charset="utf-8">
+	<title>Yesvgmap Demo</title>
+	<style>
+		body { font-family: sans-serif; margin: 2em; }
+		ul { display: grid; grid-template-columns: repeat(auto-fill, minmax(10em, 1fr)); gap: 1em; list-style: none; margin: 0; padding: 0; }
+		li { display: flex; flex-direction: column; align-items: center; gap: 0.5em; padding: 1em; border: 1px solid #ddd; border-radius: 4px; }
+		li svg { width: 48px; height: 48px; }
+		li code { font-size: 0.8em; word-break: break-all; }
+	</style>
+</head>
+<body>
+"#);
+	out.push_str(&map.render_html());
+	out.push_str("\n<ul>\n");
+	for s in map.symbols() {
+		out.push_str("\t<li><svg><use");
+		for attr in href.attrs() {
+			out.push(' ');
+			out.push_str(attr);
+			out.push_str("=\"#");
+			html_str(&mut out, s.id());
+			out.push('"');
+		}
+		out.push_str("/></svg><code>");
+		html_str(&mut out, s.id());
+		out.push_str("</code></li>\n");
+	}
+	out.push_str("</ul>\n</body>\n</html>\n");
+	out
+}
+
+/// # CSS Backgrounds.
+///
+/// Build a stylesheet with an `.icon-ID` rule for each symbol, setting its
+/// standalone image as a data-URI `background-image`.
+pub(super) fn css(map: &Map) -> String {
+	let mut out = String::new();
+	for (id, svg) in map.individual() {
+		out.push_str(".icon-");
+		out.push_str(&id);
+		out.push_str(" { background-image: url(\"data:image/svg+xml,");
+		uri_str(&mut out, &svg);
+		out.push_str("\"); }\n");
+	}
+	out
+}
+
+/// # Timing.
+///
+/// Return a summary of how long the major stages took — crawling, parsing,
+/// and serialization — along with the slowest individual source and the
+/// overall total, one per line.
+pub(super) fn timing(map: &Map, crawl: Duration, serialize: Duration, total: Duration)
+-> String {
+	let times = map.parse_times();
+	let parse: Duration = times.iter().map(|(_, d)| *d).sum();
+
+	let mut out = String::new();
+	let mut line = |label: &str, d: Duration, note: &str| {
+		let _res = write!(out, "    {label:<10} {:>10.3}ms", d.as_secs_f64() * 1000.0);
+		if ! note.is_empty() { let _res = write!(out, "  \x1b[2m({note})\x1b[0m"); }
+		out.push('\n');
+	};
+	line("crawl", crawl, "");
+	line("parse", parse, &times.len().nice_inflect("source", "sources").to_string());
+	line("serialize", serialize, "");
+
+	// Ties go to whichever came first.
+	let mut slowest: Option<&(_, Duration)> = None;
+	for t in times {
+		if slowest.is_none_or(|s| s.1 < t.1) { slowest = Some(t); }
+	}
+	if let Some((path, d)) = slowest { line("slowest", *d, &path.to_string_lossy()); }
+
+	line("total", total, "");
+	out
+}
+
+/// # Savings.
+///
+/// Return a short note comparing the final sprite size against the combined
+/// size of its sources, e.g. `1,234 bytes, down from 5,678 bytes; 78.27% saved`.
+pub(super) fn savings(map: &Map, len: usize) -> String {
+	let input = map.input_bytes();
+	let out = len.nice_inflect("byte", "bytes");
+	if input == 0 { return out.to_string(); }
+
+	let from = input.nice_inflect("byte", "bytes");
+	match NicePercent::try_from((input.saturating_sub(len), input)) {
+		Ok(pct) if len <= input => format!("{out}, down from {from}; {pct} saved"),
+		_ => format!("{out}, up from {from}"),
+	}
+}
+
+/// # Odd Sizes.
+///
+/// Find the most common `viewBox` dimensions across the map (ties going to
+/// whichever came first), and return them along with any symbols whose
+/// dimensions differ.
+///
+/// Symbols with unparseable dimensions are ignored.
+pub(super) fn odd_sizes(map: &Map) -> Option<((f64, f64), Vec<&MapSymbol>)> {
+	let mut counts: Vec<((f64, f64), usize)> = Vec::new();
+	for dims in map.symbols().iter().filter_map(MapSymbol::dimensions) {
+		if let Some(entry) = counts.iter_mut().find(|(d, _)| *d == dims) {
+			entry.1 += 1;
+		}
+		else { counts.push((dims, 1)); }
+	}
+
+	// Find the winner, preferring earlier entries in the event of a tie.
+	let mut best = *counts.first()?;
+	for &entry in counts.iter().skip(1) {
+		if best.1 < entry.1 { best = entry; }
+	}
+
+	let odd = map.symbols().iter()
+		.filter(|s| s.dimensions().is_some_and(|d| d != best.0))
+		.collect();
+	Some((best.0, odd))
+}
+
+/// # Preview.
+///
+/// Return a table listing each symbol's ID, `viewBox`, and source, along
+/// with notes about any derived or adjusted `viewBox`es and content warnings,
+/// one per line, in map order.
+pub(super) fn preview(map: &Map) -> String {
+	let id_len = map.symbols().iter().map(|s| s.id().len()).max().unwrap_or(0);
+	let vb_len = map.symbols().iter().map(|s| s.viewbox().len()).max().unwrap_or(0);
+
+	let mut out = String::new();
+	for s in map.symbols() {
+		let mut notes = Vec::new();
+		if s.derived_viewbox() { notes.push(String::from("derived viewBox")); }
+		if let Some(old) = s.rounded_viewbox() { notes.push(format!("rounded from {old}")); }
+		if ! s.warnings().is_empty() { notes.push(s.warnings().to_string()); }
+
+		let _res = write!(
+			out,
+			"{:<id_len$}  {:<vb_len$}  {}",
+			s.id(),
+			s.viewbox(),
+			s.src().display(),
+		);
+		if ! notes.is_empty() {
+			let _res = write!(out, " \x1b[2m({})\x1b[0m", notes.join("; "));
+		}
+		out.push('\n');
+	}
+	out
+}
+
+/// # Print Empty Symbols.
+///
+/// Print a warning listing any symbols with nothing to render, e.g. because
+/// all of their content was stripped as empty.
+pub(super) fn print_empty(map: &Map) {
+	let found: Vec<&MapSymbol> = map.symbols().iter()
+		.filter(|s| s.empty())
+		.collect();
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} no content:",
+		if found.len() == 1 { " has" } else { "s have" },
+	)).eprint();
+
+	for s in found {
+		eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({})\x1b[0m", s.id(), s.src().display());
+	}
+}
+
+/// # Print Odd Sizes.
+///
+/// Print a warning listing any symbols whose `viewBox` dimensions deviate
+/// from the dominant size.
+pub(super) fn print_odd_sizes(map: &Map) {
+	let Some(((w, h), odd)) = odd_sizes(map) else { return; };
+	if odd.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} deviate{} from the dominant {w}x{h} viewBox size:",
+		if odd.len() == 1 { "" } else { "s" },
+		if odd.len() == 1 { "s" } else { "" },
+	)).eprint();
+
+	for s in odd {
+		if let Some((w, h)) = s.dimensions() {
+			eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({w}x{h})\x1b[0m", s.id());
+		}
+	}
+}
+
+/// # Color Counts.
+///
+/// Tally the distinct colors used across the map, sorted by frequency (most
+/// common first), then value.
+pub(super) fn color_counts(map: &Map) -> Vec<(&str, usize)> {
+	let mut out: Vec<(&str, usize)> = Vec::new();
+	for c in map.symbols().iter().flat_map(MapSymbol::colors) {
+		if let Some(entry) = out.iter_mut().find(|(k, _)| *k == c) { entry.1 += 1; }
+		else { out.push((c, 1)); }
+	}
+	out.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+	out
+}
+
+/// # Font Counts.
+///
+/// Tally the distinct `font-family` values used across the map, sorted by
+/// frequency (most common first), then value.
+pub(super) fn font_counts(map: &Map) -> Vec<(&str, usize)> {
+	let mut out: Vec<(&str, usize)> = Vec::new();
+	for f in map.symbols().iter().flat_map(MapSymbol::fonts) {
+		if let Some(entry) = out.iter_mut().find(|(k, _)| *k == f) { entry.1 += 1; }
+		else { out.push((f, 1)); }
+	}
+	out.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+	out
+}
+
+/// # Print Colors.
+///
+/// Print the distinct colors used across the map, along with how many times
+/// each appears.
+pub(super) fn print_colors(map: &Map) {
+	let counts = color_counts(map);
+	if counts.is_empty() {
+		Msg::notice("No colors were found.").eprint();
+		return;
+	}
+
+	Msg::notice(format!(
+		"The following {} distinct color{} used:",
+		counts.len(),
+		if counts.len() == 1 { " is" } else { "s are" },
+	)).eprint();
+
+	for (c, n) in counts {
+		eprintln!("    \x1b[1;93m•\x1b[0m {c} \x1b[2m({n})\x1b[0m");
+	}
+}
+
+/// # Print Fonts.
+///
+/// Print the distinct `font-family` values used across the map, along with
+/// how many times each appears.
+pub(super) fn print_fonts(map: &Map) {
+	let counts = font_counts(map);
+	if counts.is_empty() {
+		Msg::notice("No fonts were found.").eprint();
+		return;
+	}
+
+	Msg::notice(format!(
+		"The following {} distinct font famil{} used:",
+		counts.len(),
+		if counts.len() == 1 { "y is" } else { "ies are" },
+	)).eprint();
+
+	for (f, n) in counts {
+		eprintln!("    \x1b[1;93m•\x1b[0m {f} \x1b[2m({n})\x1b[0m");
+	}
+}
+
+/// # Print Strokes.
+///
+/// Print the symbols with stroked elements lacking a `vector-effect`, which
+/// will scale along with the symbol, along with the offending tags.
+pub(super) fn print_strokes(map: &Map) {
+	let found: Vec<&MapSymbol> = map.symbols().iter()
+		.filter(|s| ! s.strokes().is_empty())
+		.collect();
+	if found.is_empty() {
+		Msg::notice("No unscaled strokes were found.").eprint();
+		return;
+	}
+
+	Msg::notice(format!(
+		"The following symbol{} use{} strokes without vector-effect=\"non-scaling-stroke\":",
+		if found.len() == 1 { "" } else { "s" },
+		if found.len() == 1 { "s" } else { "" },
+	)).eprint();
+
+	for s in found {
+		eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({})\x1b[0m", s.id(), s.strokes().join(", "));
+	}
+}
+
+/// # Print Control Characters.
+///
+/// Print a warning listing any symbols containing stray control characters,
+/// along with where they were found.
+pub(super) fn print_control_chars(map: &Map) {
+	let found: Vec<&MapSymbol> = map.symbols().iter()
+		.filter(|s| ! s.control_chars().is_empty())
+		.collect();
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} contain{} stray control characters:",
+		if found.len() == 1 { "" } else { "s" },
+		if found.len() == 1 { "s" } else { "" },
+	)).eprint();
+
+	for s in found {
+		eprintln!(
+			"    \x1b[1;93m•\x1b[0m {} \x1b[2m({})\x1b[0m",
+			s.id(),
+			s.control_chars().join(", "),
+		);
+	}
+}
+
+/// # Print Unused Namespaces.
+///
+/// Print a warning listing any symbols declaring `xmlns:*` prefixes that
+/// nothing within them uses, since the declarations can be safely removed.
+pub(super) fn print_unused_namespaces(map: &Map) {
+	let found: Vec<&MapSymbol> = map.symbols().iter()
+		.filter(|s| ! s.unused_namespaces().is_empty())
+		.collect();
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} unused namespace declarations that can be removed:",
+		if found.len() == 1 { " has" } else { "s have" },
+	)).eprint();
+
+	for s in found {
+		eprintln!(
+			"    \x1b[1;93m•\x1b[0m {} \x1b[2m({})\x1b[0m",
+			s.id(),
+			s.unused_namespaces().iter()
+				.map(|p| format!("xmlns:{p}"))
+				.collect::<Vec<_>>()
+				.join(", "),
+		);
+	}
+}
+
+/// # Directory Counts.
+///
+/// Tally the symbols by source directory, sorted by count (most first), then
+/// path.
+pub(super) fn dir_counts(map: &Map) -> Vec<(&Path, usize)> {
+	let mut out: Vec<(&Path, usize)> = Vec::new();
+	for dir in map.symbols().iter().filter_map(|s| s.src().parent()) {
+		if let Some(entry) = out.iter_mut().find(|(k, _)| *k == dir) { entry.1 += 1; }
+		else { out.push((dir, 1)); }
+	}
+	out.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+	out
+}
+
+/// # Print Directory Counts.
+///
+/// Print the number of symbols sourced from each directory.
+pub(super) fn print_dir_counts(map: &Map) {
+	let counts = dir_counts(map);
+	if counts.is_empty() { return; }
+
+	Msg::info(format!(
+		"The symbols were sourced from {} director{}:",
+		counts.len(),
+		if counts.len() == 1 { "y" } else { "ies" },
+	)).eprint();
+
+	for (dir, n) in counts {
+		eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({n})\x1b[0m", dir.display());
+	}
+}
+
+/// # Sprite Differences.
+///
+/// Compare two sets of symbols — as returned by `sprite_symbols` — returning
+/// the IDs that were added, removed, and changed (in that order) going from
+/// `old` to `new`.
+pub(super) fn sprite_diff<'a>(
+	old: &'a BTreeMap<String, String>,
+	new: &'a BTreeMap<String, String>,
+) -> (Vec<&'a str>, Vec<&'a str>, Vec<&'a str>) {
+	let added = new.keys()
+		.filter(|k| ! old.contains_key(*k))
+		.map(String::as_str)
+		.collect();
+	let removed = old.keys()
+		.filter(|k| ! new.contains_key(*k))
+		.map(String::as_str)
+		.collect();
+	let changed = new.iter()
+		.filter_map(|(k, v)| old.get(k).filter(|o| *o != v).map(|_| k.as_str()))
+		.collect();
+	(added, removed, changed)
+}
+
+/// # Print Sprite Differences.
+///
+/// Print a summary of the symbols added, removed, or changed relative to the
+/// map at `path`, returning the total number of differences.
+pub(super) fn print_sprite_diff(
+	old: &BTreeMap<String, String>,
+	new: &BTreeMap<String, String>,
+	path: &Path,
+) -> usize {
+	let (added, removed, changed) = sprite_diff(old, new);
+	let total = added.len() + removed.len() + changed.len();
+	if total == 0 {
+		Msg::info(format!("The symbols are identical to {}.", path.display())).eprint();
+		return 0;
+	}
+
+	Msg::notice(format!(
+		"Compared with {}: {} added, {} removed, {} changed.",
+		path.display(),
+		added.len(),
+		removed.len(),
+		changed.len(),
+	)).eprint();
+
+	for id in added { eprintln!("    \x1b[1;92m+\x1b[0m {id}"); }
+	for id in removed { eprintln!("    \x1b[1;91m-\x1b[0m {id}"); }
+	for id in changed { eprintln!("    \x1b[1;93m~\x1b[0m {id}"); }
+
+	total
+}
+
+/// # Print Derived `viewBox`es.
+///
+/// Print a notice listing any symbols whose `viewBox` had to be derived from
+/// the source's `width` and `height`.
+pub(super) fn print_derived_viewboxes(map: &Map) {
+	let derived: Vec<&MapSymbol> = map.symbols().iter()
+		.filter(|s| s.derived_viewbox())
+		.collect();
+	if derived.is_empty() { return; }
+
+	Msg::notice(format!(
+		"The following symbol{} had no viewBox of {} own; one was derived from the width and height:",
+		if derived.len() == 1 { "" } else { "s" },
+		if derived.len() == 1 { "its" } else { "their" },
+	)).eprint();
+
+	for s in derived {
+		eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({})\x1b[0m", s.id(), s.src().display());
+	}
+}
+
+/// # Path List.
+///
+/// Return a listing of each included source path and the resulting symbol ID,
+/// one per line, in map order.
+pub(super) fn path_list(map: &Map) -> String {
+	let mut out = String::new();
+	for s in map.symbols() {
+		let _res = writeln!(out, "{} \x1b[2m→\x1b[0m {}", s.src().display(), s.id());
+	}
+	out
+}
+
+/// # Print Paths.
+///
+/// Print the included source paths and their symbol IDs to STDERR.
+pub(super) fn print_paths(map: &Map) {
+	Msg::info(format!(
+		"The following {} included:",
+		if map.len() == 1 { "source was" } else { "sources were" },
+	)).eprint();
+	eprint!("{}", path_list(map));
+}
+
+/// # Print Rounded `viewBox`es.
+///
+/// Print a warning listing any symbols whose fractional `viewBox` dimensions
+/// were rounded to whole numbers.
+pub(super) fn print_rounded_viewboxes(map: &Map) {
+	let found: Vec<(&MapSymbol, &str)> = map.symbols().iter()
+		.filter_map(|s| s.rounded_viewbox().map(|old| (s, old)))
+		.collect();
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} had fractional viewBox dimensions that were rounded:",
+		if found.len() == 1 { "" } else { "s" },
+	)).eprint();
+
+	for (s, old) in found {
+		eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({old} → {})\x1b[0m", s.id(), s.viewbox());
+	}
+}
+
+/// # Print Fixed Dimensions.
+///
+/// Print a warning listing any symbols whose source root has absolute `width`
+/// or `height` units alongside a `viewBox`.
+pub(super) fn print_fixed_dimensions(map: &Map) {
+	let found: Vec<(&MapSymbol, &str)> = map.symbols().iter()
+		.filter_map(|s| s.fixed_dimensions().map(|d| (s, d)))
+		.collect();
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} fixed dimensions that will apply if the source is used directly:",
+		if found.len() == 1 { " has" } else { "s have" },
+	)).eprint();
+
+	for (s, dims) in found {
+		eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({dims})\x1b[0m", s.id());
+	}
+}
+
+/// # Print Title Mismatches.
+///
+/// Print a warning listing any symbols whose `<title>` doesn't seem to match
+/// the source file name.
+pub(super) fn print_title_mismatches(map: &Map) {
+	let found: Vec<(&MapSymbol, &str)> = map.symbols().iter()
+		.filter_map(|s| s.title_mismatch().map(|t| (s, t)))
+		.collect();
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} {} a <title> that doesn't match the file name:",
+		if found.len() == 1 { "" } else { "s" },
+		if found.len() == 1 { "has" } else { "have" },
+	)).eprint();
+
+	for (s, title) in found {
+		eprintln!("    \x1b[1;93m•\x1b[0m {} \x1b[2m({title:?})\x1b[0m", s.id());
+	}
+}
+
+/// # Print Zero-Dimension Shapes.
+///
+/// Print a warning listing any symbols containing shapes with zero-valued
+/// dimensions, along with where they were found.
+pub(super) fn print_zero_shapes(map: &Map) {
+	let found: Vec<&MapSymbol> = map.symbols().iter()
+		.filter(|s| ! s.zero_shapes().is_empty())
+		.collect();
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} contain{} zero-dimension shapes that will not render:",
+		if found.len() == 1 { "" } else { "s" },
+		if found.len() == 1 { "s" } else { "" },
+	)).eprint();
+
+	for s in found {
+		eprintln!(
+			"    \x1b[1;93m•\x1b[0m {} \x1b[2m({})\x1b[0m",
+			s.id(),
+			s.zero_shapes().join(", "),
+		);
+	}
+}
+
+/// # Unreferenced Symbols.
+///
+/// Return any symbols whose IDs are missing from the `used` set.
+pub(super) fn unreferenced<'a>(map: &'a Map, used: &BTreeSet<String>)
+-> Vec<&'a MapSymbol> {
+	map.symbols().iter()
+		.filter(|s| ! used.contains(s.id()))
+		.collect()
+}
+
+/// # ID Lock.
+///
+/// Merge the map's symbol IDs into an existing lockfile — one `id<TAB>owner`
+/// pair per line — on behalf of `owner`, returning the updated contents.
+///
+/// Any prior claims by `owner` are released first so IDs dropped from the
+/// sprite become available again. If any of the map's IDs are claimed by a
+/// different owner, those `(id, owner)` pairs are returned instead.
+pub(super) fn id_lock(map: &Map, owner: &str, src: &str)
+-> Result<String, Vec<(String, String)>> {
+	let mut lock: BTreeMap<&str, &str> = src.lines()
+		.filter_map(|line| {
+			let (id, who) = line.split_once('\t')?;
+			let id = id.trim();
+			if id.is_empty() { None }
+			else { Some((id, who.trim())) }
+		})
+		.filter(|(_, who)| *who != owner)
+		.collect();
+
+	let mut claimed = Vec::new();
+	for s in map.symbols() {
+		if let Some(who) = lock.get(s.id()) {
+			claimed.push((s.id().to_owned(), (*who).to_owned()));
+		}
+		else { lock.insert(s.id(), owner); }
+	}
+
+	if claimed.is_empty() {
+		let mut out = String::new();
+		for (id, who) in lock {
+			out.push_str(id);
+			out.push('\t');
+			out.push_str(who);
+			out.push('\n');
+		}
+		Ok(out)
+	}
+	else { Err(claimed) }
+}
+
+/// # Parse Used IDs.
+///
+/// Parse a newline-delimited list of symbol IDs, ignoring blank lines.
+pub(super) fn parse_used_ids(src: &str) -> BTreeSet<String> {
+	src.lines()
+		.map(str::trim)
+		.filter(|line| ! line.is_empty())
+		.map(String::from)
+		.collect()
+}
+
+/// # Print Unreferenced Symbols.
+///
+/// Print a warning listing any symbols that do not appear in the `used` set,
+/// i.e. candidates for pruning.
+pub(super) fn print_unreferenced(map: &Map, used: &BTreeSet<String>) {
+	let found = unreferenced(map, used);
+	if found.is_empty() { return; }
+
+	Msg::warning(format!(
+		"The following symbol{} never used:",
+		if found.len() == 1 { " is" } else { "s are" },
+	)).eprint();
+
+	for s in found {
+		eprintln!("    \x1b[1;93m•\x1b[0m {}", s.id());
+	}
+}
+
+/// # Warning Count.
+///
+/// Return the total number of content warnings across all files, counting
+/// each category separately, e.g. a file with both `style` and `class` issues
+/// counts twice.
+pub(super) fn warning_count(map: &Map) -> usize {
+	map.warnings().iter().map(|(_, w)| w.names().count()).sum()
+}
+
+/// # Warning Summary.
+///
+/// Aggregate the map's content warnings by category, returning each affected
+/// category (in display order) along with the paths of the files it applies
+/// to.
+pub(super) fn warning_summary(map: &Map) -> Vec<(&'static str, Vec<&Path>)> {
+	ContentWarnings::categories()
+		.filter_map(|(flag, name)| {
+			let paths: Vec<&Path> = map.warnings().iter()
+				.filter_map(|(p, w)| if w.contains(flag) { Some(p.as_path()) } else { None })
+				.collect();
+			if paths.is_empty() { None }
+			else { Some((name, paths)) }
+		})
+		.collect()
+}
+
+/// # Print Warning Summary.
+///
+/// Print the aggregated content warnings, if any, to STDERR. This is an
+/// alternative to the default per-file listing.
+pub(super) fn print_warning_summary(map: &Map) {
+	/// # Maximum Paths Per Category.
+	const MAX: usize = 3;
+
+	let summary = warning_summary(map);
+	if summary.is_empty() { return; }
+
+	Msg::warning(format!(
+		"Potentially problematic content was found in {} image{}:",
+		map.warnings().len(),
+		if map.warnings().len() == 1 { "" } else { "s" },
+	)).eprint();
+
+	for (name, paths) in summary {
+		let mut list: Vec<String> = paths.iter()
+			.take(MAX)
+			.map(|p| p.file_name().map_or_else(
+				|| p.to_string_lossy(),
+				|n| n.to_string_lossy(),
+			).into_owned())
+			.collect();
+		if MAX < paths.len() { list.push(format!("and {} more", paths.len() - MAX)); }
+
+		eprintln!(
+			"    \x1b[1;93m•\x1b[0m {name}: {} \x1b[2m({})\x1b[0m",
+			paths.len(),
+			list.join(", "),
+		);
+	}
+}
+
+/// # Push HTML String.
+///
+/// Push an escaped string — safe for text or (quoted) attribute values — onto
+/// the buffer.
+fn html_str(out: &mut String, src: &str) {
+	for c in src.chars() {
+		match c {
+			'&' => out.push_str("&amp;"),
+			'<' => out.push_str("&lt;"),
+			'>' => out.push_str("&gt;"),
+			'"' => out.push_str("&quot;"),
+			'\'' => out.push_str("&#39;"),
+			c => out.push(c),
+		}
+	}
+}
+
+/// # Push Data-URI String.
+///
+/// Push a minimally percent-encoded string onto the buffer, suitable for use
+/// in a (double-quoted) CSS `url()`. Aside from the characters significant to
+/// URIs and the quoting, only `%` itself, backslashes, and line breaks — which
+/// CSS strings can't hold as-is — are encoded.
+fn uri_str(out: &mut String, src: &str) {
+	for c in src.chars() {
+		match c {
+			'"' => out.push_str("%22"),
+			'#' => out.push_str("%23"),
+			'%' => out.push_str("%25"),
+			'<' => out.push_str("%3C"),
+			'>' => out.push_str("%3E"),
+			'\\' => out.push_str("%5C"),
+			'\n' => out.push_str("%0A"),
+			'\r' => out.push_str("%0D"),
+			c => out.push(c),
+		}
+	}
+}
+
+/// # Push JSON String List.
+///
+/// Push a comma-separated list of JSON strings onto the buffer. (The brackets
+/// are left to the caller.)
+fn json_list<'a, I: Iterator<Item=&'a str>>(out: &mut String, src: I) {
+	for (idx, s) in src.enumerate() {
+		if idx != 0 { out.push(','); }
+		json_str(out, s);
+	}
+}
+
+/// # Push JSON String.
+///
+/// Push a quoted and escaped JSON string onto the buffer.
+fn json_str(out: &mut String, src: &str) {
+	out.push('"');
+	for c in src.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if c.is_control() => {
+				let _res = write!(out, "\\u{:04x}", u32::from(c));
+			},
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		MapOptions,
+		img::sprite_symbols,
+	};
+	use std::path::PathBuf;
+
+	#[test]
+	fn test_savings() {
+		let sources = [
+			("i-a", r#"<svg viewBox="0 0 10 10" version="1.1"><!-- Comment! --><path d="M0 0h10"/></svg>"#),
+			("i-b", r#"<svg viewBox="0 0 10 10"><path d="M0 0h5"/></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(map.input_bytes(), sources[0].1.len() + sources[1].1.len());
+		assert_eq!(map.input_bytes(), 130);
+
+		assert_eq!(savings(&map, 100), "100 bytes, down from 130 bytes; 23.08% saved");
+		assert_eq!(savings(&map, 130), "130 bytes, down from 130 bytes; 0.00% saved");
+		assert_eq!(savings(&map, 1234), "1,234 bytes, up from 130 bytes");
+	}
+
+	#[test]
+	fn test_odd_sizes() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-2.svg"),
+			PathBuf::from("test-assets/arrow-3.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let Some((dims, odd)) = odd_sizes(&map) else {
+			panic!("BUG: no sizes found.");
+		};
+		assert_eq!(dims, (477.175, 477.175));
+		assert_eq!(odd.len(), 1);
+		assert_eq!(odd[0].id(), "i-close");
+	}
+
+	#[test]
+	fn test_color_counts() {
+		let sources = [
+			("i-a", r##"<svg viewBox="0 0 10 10"><path fill="#F00" stroke="none" d="M0 0h10v10H0z"/><path style="fill: #ff0000; stroke: Blue" d="M0 0h5v5H0z"/></svg>"##),
+			("i-b", r#"<svg viewBox="0 0 10 10" fill="currentColor"><linearGradient id="g"><stop stop-color="rgb(0, 0, 255)"/><stop stop-color="blue"/></linearGradient><path fill="url(#g)" d="M0 0h10v10H0z"/></svg>"#),
+		];
+
+		// Nothing unless asked.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert!(color_counts(&map).is_empty());
+
+		let opts = MapOptions { report_colors: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			color_counts(&map),
+			[("#ff0000", 2), ("blue", 2), ("rgb(0,0,255)", 1)],
+		);
+	}
+
+	#[test]
+	fn test_font_counts() {
+		let sources = [
+			("i-a", r#"<svg viewBox="0 0 10 10"><text font-family="Comic Sans,  cursive" x="1">A</text><text style="fill: red; font-family: Georgia, serif !important" x="5">B</text></svg>"#),
+			("i-b", r#"<svg viewBox="0 0 10 10"><g font-family="Comic Sans, cursive"><text x="1">C</text></g><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+
+		// Nothing unless asked.
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert!(font_counts(&map).is_empty());
+
+		let opts = MapOptions { report_fonts: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			font_counts(&map),
+			[("Comic Sans, cursive", 2), ("Georgia, serif", 1)],
+		);
+	}
+
+	#[test]
+	fn test_demo() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/arrow-2.svg"),
+		];
+		let opts = MapOptions { prefix: "x".to_owned(), ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &paths, &[]).expect("Map failed.");
+		let html = demo(&map, HrefStyle::Href);
+
+		assert!(html.starts_with("<!DOCTYPE html>\n"));
+		assert!(html.ends_with("</html>\n"));
+
+		// The map should be inlined (and hidden).
+		assert!(html.contains(&format!(
+			r#"<div style="display:none" aria-hidden="true">{map}</div>"#,
+		)));
+
+		// With one reference per symbol, using the real IDs.
+		assert!(html.contains(r##"<li><svg><use href="#x-arrow-1"/></svg><code>x-arrow-1</code></li>"##));
+		assert!(html.contains(r##"<li><svg><use href="#x-arrow-2"/></svg><code>x-arrow-2</code></li>"##));
+		assert_eq!(html.matches("<use ").count(), 2);
+
+		// The references can be written other ways too.
+		assert_eq!(HrefStyle::from_name(" both "), Some(HrefStyle::Both));
+		assert_eq!(HrefStyle::from_name("xlink:href"), None);
+		let html = demo(&map, HrefStyle::Xlink);
+		assert!(html.contains(r##"<li><svg><use xlink:href="#x-arrow-1"/></svg>"##));
+		assert!(! html.contains("<use href"));
+		let html = demo(&map, HrefStyle::Both);
+		assert!(html.contains(r##"<li><svg><use href="#x-arrow-1" xlink:href="#x-arrow-1"/></svg>"##));
+
+		let mut out = String::new();
+		html_str(&mut out, r#"<a href="x">Tom & Jerry's</a>"#);
+		assert_eq!(out, "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+	}
+
+	#[test]
+	fn test_css() {
+		let sources = [
+			("i-dot", r##"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4" fill="#f00"/></svg>"##),
+			("i-box", r#"<svg viewBox="0 0 24 24"><path d="M0 0h24v24H0z"/></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(
+			css(&map),
+			concat!(
+				r#".icon-i-box { background-image: url("data:image/svg+xml,"#,
+				"%3Csvg viewBox=%220 0 24 24%22 xmlns=%22http://www.w3.org/2000/svg%22%3E",
+				"%3Cpath d=%22M0 0h24v24H0z%22/%3E%3C/svg%3E\"); }\n",
+				r#".icon-i-dot { background-image: url("data:image/svg+xml,"#,
+				"%3Csvg viewBox=%220 0 10 10%22 xmlns=%22http://www.w3.org/2000/svg%22%3E",
+				"%3Ccircle cx=%225%22 cy=%225%22 fill=%22%23f00%22 r=%224%22/%3E%3C/svg%3E\"); }\n",
+			),
+		);
+
+		let mut out = String::new();
+		uri_str(&mut out, "<a title=\"50%\">\\\n</a>");
+		assert_eq!(out, "%3Ca title=%2250%25%22%3E%5C%0A%3C/a%3E");
+	}
+
+	#[test]
+	fn test_derived_viewbox() {
+		let sources = [
+			("i-explicit", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-derived", r#"<svg width="10" height="10"><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources)
+			.expect("Map failed.");
+		let derived: Vec<(&str, bool)> = map.symbols().iter()
+			.map(|s| (s.id(), s.derived_viewbox()))
+			.collect();
+		assert_eq!(derived, [("i-derived", true), ("i-explicit", false)]);
+
+		// The fixtures all have explicit viewBoxes.
+		let map = Map::with_sources(&MapOptions::default(), &[PathBuf::from("test-assets/close.svg")], &[])
+			.expect("Map failed.");
+		assert!(! map.symbols()[0].derived_viewbox(), "BUG: close.svg viewBox was derived.");
+	}
+
+	#[test]
+	fn test_dir_counts() {
+		let dir = std::env::temp_dir().join("yesvgmap-tests").join("report-by-dir");
+		let _res = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(dir.join("nav")).expect("Unable to create temporary directory.");
+		std::fs::create_dir_all(dir.join("social")).expect("Unable to create temporary directory.");
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
+		for name in ["close.svg", "nav/back.svg", "nav/home.svg", "nav/menu.svg", "social/bsky.svg", "social/rss.svg"] {
+			std::fs::write(dir.join(name), raw).expect("Unable to write temporary file.");
+		}
+
+		let paths = dowser::Dowser::default()
+			.with_path(&dir)
+			.into_vec_filtered(|p| p.extension().is_some_and(|e| e == "svg"));
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let dir = std::fs::canonicalize(&dir).expect("Missing directory.");
+		let nav = dir.join("nav");
+		let social = dir.join("social");
+		assert_eq!(
+			dir_counts(&map),
+			[(nav.as_path(), 3), (social.as_path(), 2), (dir.as_path(), 1)],
+		);
+	}
+
+	#[test]
+	fn test_empty() {
+		let sources = [
+			("i-cruft", r#"<svg viewBox="0 0 10 10"><title>Cruft</title><g></g><defs/><metadata/></svg>"#),
+			("i-dot", r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#),
+			("i-nothing", r#"<svg viewBox="0 0 10 10"></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		let empty: Vec<&str> = map.symbols().iter()
+			.filter(|s| s.empty())
+			.map(MapSymbol::id)
+			.collect();
+		assert_eq!(empty, ["i-cruft", "i-nothing"]);
+	}
+
+	#[test]
+	fn test_id_lock() {
+		let sources = [
+			("i-close", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-menu", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+		let first = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+
+		// The first run claims its IDs.
+		let lock = id_lock(&first, "a.svg", "").expect("Lock failed.");
+		assert_eq!(lock, "i-close\ta.svg\ni-menu\ta.svg\n");
+
+		// Re-running the same sprite is fine, and releases dropped IDs.
+		let smaller = Map::with_sources(&MapOptions::default(), &[], &sources[..1])
+			.expect("Map failed.");
+		assert_eq!(
+			id_lock(&smaller, "a.svg", &lock).as_deref(),
+			Ok("i-close\ta.svg\n"),
+		);
+
+		// A second sprite sharing an ID collides.
+		let sources = [
+			("i-menu", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-star", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+		let second = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(
+			id_lock(&second, "b.svg", &lock),
+			Err(vec![(String::from("i-menu"), String::from("a.svg"))]),
+		);
+
+		// But not once the ID has been released.
+		let lock = id_lock(&smaller, "a.svg", &lock).expect("Lock failed.");
+		assert_eq!(
+			id_lock(&second, "b.svg", &lock).as_deref(),
+			Ok("i-close\ta.svg\ni-menu\tb.svg\ni-star\tb.svg\n"),
+		);
+	}
+
+	#[test]
+	fn test_json_str() {
+		let mut out = String::new();
+		json_str(&mut out, "Hello \"World\"\\\n\u{1}");
+		assert_eq!(out, r#""Hello \"World\"\\\n\u0001""#);
+	}
+
+	#[test]
+	fn test_json_report() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-3.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let report = json_report(&map, 123);
+		assert_eq!(
+			report,
+			concat!(
+				r#"{"symbols":["#,
+				r#"{"id":"i-arrow-3","source":"test-assets/arrow-3.svg","viewBox":"0 0 477.175 477.175","width":477.175,"height":477.175,"warnings":["id"]},"#,
+				r#"{"id":"i-close","source":"test-assets/close.svg","viewBox":"0 0 512.001 512.001","width":512.001,"height":512.001,"warnings":[]}"#,
+				r#"],"warnings":["#,
+				r#"{"source":"test-assets/arrow-3.svg","categories":["id"]}"#,
+				r#"],"stats":{"symbols":2,"warned":1,"bytes":123}}"#,
+			),
+		);
+	}
+
+	#[test]
+	fn test_json_report_schema() {
+		use serde_json::Value;
+
+		/// # Check Keys.
+		///
+		/// Make sure the object's keys match the schema's properties and
+		/// required list exactly, recursing into nested objects and arrays.
+		fn check(schema: &Value, value: &Value, defs: &Value) {
+			let schema = schema.get("$ref")
+				.and_then(Value::as_str)
+				.map_or(schema, |r| &defs[r.trim_start_matches("#/$defs/")]);
+			match value {
+				Value::Object(obj) => {
+					let props = schema["properties"].as_object().expect("Missing properties.");
+					let mut keys: Vec<&str> = obj.keys().map(String::as_str).collect();
+					let mut expected: Vec<&str> = props.keys().map(String::as_str).collect();
+					keys.sort_unstable();
+					expected.sort_unstable();
+					assert_eq!(keys, expected, "Schema/report key mismatch.");
+
+					let mut required: Vec<&str> = schema["required"].as_array()
+						.expect("Missing required.")
+						.iter()
+						.filter_map(Value::as_str)
+						.collect();
+					required.sort_unstable();
+					assert_eq!(required, expected, "Not all keys are required.");
+
+					for (k, v) in obj { check(&props[k], v, defs); }
+				},
+				Value::Array(list) => {
+					for v in list { check(&schema["items"], v, defs); }
+				},
+				Value::String(s) if schema["items"].is_null() && ! schema["enum"].is_null() => {
+					assert!(schema["enum"].as_array().expect("Bad enum.").contains(value), "Unknown category {s}.");
+				},
+				_ => {},
+			}
+		}
+
+		let schema: Value = serde_json::from_str(&json_report_schema())
+			.expect("The schema is not valid JSON.");
+		assert_eq!(schema["type"], "object");
+
+		// Every category should be listed.
+		let categories = schema["$defs"]["categories"]["items"]["enum"].as_array()
+			.expect("Missing categories.");
+		assert_eq!(categories.len(), ContentWarnings::categories().count());
+
+		// And it should match up with an actual report.
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/close.svg"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let report: Value = serde_json::from_str(&json_report(&map, 123))
+			.expect("The report is not valid JSON.");
+		assert!(! report["warnings"].as_array().expect("Missing warnings.").is_empty());
+		check(&schema, &report, &schema["$defs"]);
+	}
+
+	#[test]
+	fn test_preview() {
+		let sources = [
+			("i-derived", r#"<svg width="12" height="12"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-dot", r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#),
+			("i-half", r#"<svg viewBox="0 0 23.5 24"><path class="a" d="M0 0h10v10H0z"/></svg>"#),
+		];
+		let opts = MapOptions { integer_viewbox: true, ..MapOptions::default() };
+		let map = Map::with_sources(&opts, &[], &sources).expect("Map failed.");
+		assert_eq!(
+			preview(&map),
+			concat!(
+				"i-derived  0 0 12 12  i-derived \x1b[2m(derived viewBox)\x1b[0m\n",
+				"i-dot      0 0 10 10  i-dot\n",
+				"i-half     0 0 24 24  i-half \x1b[2m(rounded from 0 0 23.5 24; class)\x1b[0m\n",
+			),
+		);
+	}
+
+	#[test]
+	fn test_sprite_diff() {
+		let old = Map::with_sources(&MapOptions::default(), &[], &[
+			("i-close", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-menu", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v1H0z"/></svg>"#),
+			("i-star", r#"<svg viewBox="0 0 10 10"><path d="M5 0l5 10H0z"/></svg>"#),
+		]).expect("Map failed.").to_string();
+		let new = Map::with_sources(&MapOptions::default(), &[], &[
+			("i-close", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-menu", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v2H0z"/></svg>"#),
+			("i-search", r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#),
+		]).expect("Map failed.").to_string();
+
+		let old = sprite_symbols(&old).expect("Unable to read sprite.");
+		let new = sprite_symbols(&new).expect("Unable to read sprite.");
+		assert_eq!(old.len(), 3);
+		assert_eq!(
+			sprite_diff(&old, &new),
+			(vec!["i-search"], vec!["i-star"], vec!["i-menu"]),
+		);
+
+		// No differences.
+		assert_eq!(sprite_diff(&new, &new), (Vec::new(), Vec::new(), Vec::new()));
+	}
+
+	#[test]
+	fn test_unreferenced() {
+		let sources = [
+			("i-close", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-menu", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-search", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+			("i-star", r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+
+		// Blank lines and stray whitespace are ignored, as are IDs that
+		// aren't in the map.
+		let used = parse_used_ids("i-menu\n\n  i-star \r\ni-missing\n");
+		assert_eq!(used.len(), 3);
+
+		let unused: Vec<&str> = unreferenced(&map, &used).into_iter()
+			.map(MapSymbol::id)
+			.collect();
+		assert_eq!(unused, ["i-close", "i-search"]);
+
+		// Nothing unused.
+		let used = parse_used_ids("i-close\ni-menu\ni-search\ni-star");
+		assert!(unreferenced(&map, &used).is_empty());
+	}
+
+	#[test]
+	fn test_warning_count() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/arrow-2.svg"),
+			PathBuf::from("test-assets/arrow-3.svg"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let count = warning_count(&map);
+		assert_eq!(count, 4);
+
+		// A cap of three should be exceeded.
+		let max = 3;
+		assert!(max < count);
+		assert_eq!(
+			crate::SvgError::TooManyWarnings(count, max).to_string(),
+			"Found 4 content warnings, exceeding the maximum of 3.",
+		);
+
+		// Clean maps have none.
+		let sources = [
+			("i-dot", r#"<svg viewBox="0 0 10 10"><circle cx="5" cy="5" r="4"/></svg>"#),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &[], &sources).expect("Map failed.");
+		assert_eq!(warning_count(&map), 0);
+	}
+
+	#[test]
+	fn test_warning_summary() {
+		let paths = [
+			PathBuf::from("test-assets/arrow-1.svg"),
+			PathBuf::from("test-assets/arrow-2.svg"),
+			PathBuf::from("test-assets/arrow-3.svg"),
+		];
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let summary: Vec<(&str, usize)> = warning_summary(&map).into_iter()
+			.map(|(name, paths)| (name, paths.len()))
+			.collect();
+		assert_eq!(summary, [("style", 1), ("class", 2), ("id", 1)]);
+	}
+
+	#[test]
+	fn test_path_list() {
+		let dir = std::env::temp_dir().join("yesvgmap-tests").join("report-paths");
+		let _res = std::fs::remove_dir_all(&dir);
+		std::fs::create_dir_all(dir.join("sub")).expect("Unable to create temporary directory.");
+		let raw = r#"<svg viewBox="0 0 10 10"><path d="M0 0h10v10H0z"/></svg>"#;
+		for name in ["b.svg", "sub/a.svg", "notes.txt"] {
+			std::fs::write(dir.join(name), raw).expect("Unable to write temporary file.");
+		}
+
+		let paths = dowser::Dowser::default()
+			.with_path(&dir)
+			.into_vec_filtered(|p| p.extension().is_some_and(|e| e == "svg"));
+		let map = Map::with_sources(&MapOptions::default(), &paths, &[]).expect("Map failed.");
+		let dir = std::fs::canonicalize(&dir).expect("Missing directory.");
+		assert_eq!(
+			path_list(&map),
+			format!(
+				"{} \x1b[2m→\x1b[0m i-a\n{} \x1b[2m→\x1b[0m i-b\n",
+				dir.join("sub/a.svg").display(),
+				dir.join("b.svg").display(),
+			),
+		);
+	}
+}
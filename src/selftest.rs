@@ -0,0 +1,109 @@
+/*!
+# Yesvgmap: Self-Test
+*/
+
+use crate::{
+	img::{
+		parse_stem_id,
+		parse_svg,
+	},
+	MapOptions,
+	SvgError,
+};
+use fyi_msg::Msg;
+use std::path::Path;
+
+
+
+/// # Fixtures.
+///
+/// The embedded source images and their expected (default) symbol output.
+const FIXTURES: [(&str, &str, &str); 6] = [
+	(
+		"arrow-1.svg",
+		include_str!("../test-assets/arrow-1.svg"),
+		include_str!("../test-assets/expected/arrow-1.txt"),
+	),
+	(
+		"arrow-2.svg",
+		include_str!("../test-assets/arrow-2.svg"),
+		include_str!("../test-assets/expected/arrow-2.txt"),
+	),
+	(
+		"arrow-3.svg",
+		include_str!("../test-assets/arrow-3.svg"),
+		include_str!("../test-assets/expected/arrow-3.txt"),
+	),
+	(
+		"bitcoin.svg",
+		include_str!("../test-assets/bitcoin.svg"),
+		include_str!("../test-assets/expected/bitcoin.txt"),
+	),
+	(
+		"close.svg",
+		include_str!("../test-assets/close.svg"),
+		include_str!("../test-assets/expected/close.txt"),
+	),
+	(
+		"up.svg",
+		include_str!("../test-assets/up.svg"),
+		include_str!("../test-assets/expected/up.txt"),
+	),
+];
+
+
+
+/// # Self-Test.
+///
+/// Run each of the embedded fixtures through the parser, printing a pass/fail
+/// line for each. An error is returned if any of them fail to match their
+/// expected output.
+pub(super) fn selftest() -> Result<(), SvgError> {
+	let mut failed: usize = 0;
+	for (name, src, expected) in FIXTURES {
+		if check(name, src, expected) {
+			Msg::custom("Pass", 10, name).with_newline(true).eprint();
+		}
+		else {
+			failed += 1;
+			Msg::custom("Fail", 9, name).with_newline(true).eprint();
+		}
+	}
+
+	if failed == 0 {
+		Msg::success(format!("All {} fixtures passed.", FIXTURES.len())).eprint();
+		Ok(())
+	}
+	else { Err(SvgError::SelfTest(failed)) }
+}
+
+/// # Check Fixture.
+///
+/// Returns `true` if the source parses into the expected symbol.
+fn check(name: &str, src: &str, expected: &str) -> bool {
+	let path = Path::new(name);
+	parse_stem_id(path)
+		.and_then(|stem| {
+			let id = format!("i-{stem}");
+			parse_svg(src, path, &id, &MapOptions::default()).ok()
+		})
+		.is_some_and(|(symbol, _, _)| symbol.to_string() == expected.trim_end())
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_selftest() {
+		for (name, src, expected) in FIXTURES {
+			assert!(check(name, src, expected), "Fixture failed: {name}");
+		}
+
+		// And make sure failures fail.
+		let (name, src, _) = FIXTURES[0];
+		assert!(! check(name, src, "<symbol/>"), "BUG: bad fixture passed.");
+	}
+}
@@ -0,0 +1,216 @@
+/*!
+# Yesvgmap: Warnings
+*/
+
+use std::{
+	fmt,
+	ops::{
+		BitAnd,
+		BitOr,
+		BitOrAssign,
+	},
+};
+
+
+
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+/// # Content Warnings.
+///
+/// This is a simple bitflag holding the different kinds of potentially
+/// problematic content found in a source image.
+pub(super) struct ContentWarnings(u16);
+
+impl BitAnd for ContentWarnings {
+	type Output = Self;
+	fn bitand(self, rhs: Self) -> Self::Output { Self(self.0 & rhs.0) }
+}
+
+impl BitOr for ContentWarnings {
+	type Output = Self;
+	fn bitor(self, rhs: Self) -> Self::Output { Self(self.0 | rhs.0) }
+}
+
+impl BitOrAssign for ContentWarnings {
+	fn bitor_assign(&mut self, rhs: Self) { self.0 |= rhs.0; }
+}
+
+impl fmt::Display for ContentWarnings {
+	/// # Display.
+	///
+	/// Print the (comma-separated) category names.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut first = true;
+		for name in self.names() {
+			if first { first = false; }
+			else { f.write_str(", ")?; }
+			f.write_str(name)?;
+		}
+		Ok(())
+	}
+}
+
+impl ContentWarnings {
+	/// # None.
+	pub(super) const NONE: Self = Self(0);
+
+	/// # Scripts.
+	///
+	/// This covers both `<script>` elements and `on*` event attributes.
+	pub(super) const SCRIPTS: Self = Self(0b0000_0001);
+
+	/// # Style Elements.
+	pub(super) const STYLES: Self = Self(0b0000_0010);
+
+	/// # Inline Style Attributes.
+	pub(super) const INLINE_STYLES: Self = Self(0b0000_0100);
+
+	/// # Class Attributes.
+	pub(super) const CLASSES: Self = Self(0b0000_1000);
+
+	/// # ID Attributes.
+	pub(super) const IDS: Self = Self(0b0001_0000);
+
+	/// # External References.
+	pub(super) const EXTERNAL_REFS: Self = Self(0b0010_0000);
+
+	/// # Data URIs.
+	pub(super) const DATA_URIS: Self = Self(0b0100_0000);
+
+	/// # Raster Images.
+	pub(super) const RASTERS: Self = Self(0b1000_0000);
+
+	/// # Duplicate Attributes.
+	///
+	/// This is set when an element specifies the same attribute more than
+	/// once; only the last value is kept.
+	pub(super) const DUPLICATE_ATTRS: Self = Self(0b0000_0001_0000_0000);
+
+	/// # Text Content.
+	///
+	/// This is set when a `<text>`, `<tspan>`, or `<textPath>` has actual
+	/// text, the rendering of which depends on font availability.
+	pub(super) const TEXT: Self = Self(0b0000_0010_0000_0000);
+
+	/// # All.
+	pub(super) const ALL: Self = Self(0b0000_0011_1111_1111);
+
+	/// # External Resources.
+	///
+	/// This is a convenience grouping of the external reference, data URI,
+	/// and raster image flags.
+	pub(super) const EXTERNAL: Self = Self(
+		Self::EXTERNAL_REFS.0 | Self::DATA_URIS.0 | Self::RASTERS.0
+	);
+
+	/// # Names.
+	///
+	/// The CLI-facing category names, in display order.
+	const NAMES: [(Self, &'static str); 10] = [
+		(Self::SCRIPTS, "script"),
+		(Self::STYLES, "style"),
+		(Self::INLINE_STYLES, "inline-style"),
+		(Self::CLASSES, "class"),
+		(Self::IDS, "id"),
+		(Self::EXTERNAL_REFS, "external-ref"),
+		(Self::DATA_URIS, "data-uri"),
+		(Self::RASTERS, "raster"),
+		(Self::DUPLICATE_ATTRS, "duplicate-attr"),
+		(Self::TEXT, "text"),
+	];
+
+	/// # From Name.
+	///
+	/// Return the flag corresponding to a single (CLI) category name, if any.
+	pub(super) fn from_name(src: &str) -> Option<Self> {
+		let src = src.trim();
+		Self::NAMES.iter()
+			.find_map(|(flag, name)|
+				if src.eq_ignore_ascii_case(name) { Some(*flag) }
+				else { None }
+			)
+	}
+
+	/// # From List.
+	///
+	/// Parse a comma-separated list of category names, returning `None` if
+	/// any are invalid.
+	pub(super) fn from_list(src: &str) -> Option<Self> {
+		let mut out = Self::NONE;
+		for part in src.split(',') {
+			out |= Self::from_name(part)?;
+		}
+		Some(out)
+	}
+
+	/// # Contains?
+	///
+	/// Returns `true` if any of the flags in `other` are also set on `self`.
+	pub(super) const fn contains(self, other: Self) -> bool {
+		self.0 & other.0 != 0
+	}
+
+	/// # Is Empty?
+	pub(super) const fn is_empty(self) -> bool { self.0 == 0 }
+
+	/// # Without.
+	///
+	/// Return a copy of `self` with any of the flags in `other` unset.
+	pub(super) const fn without(self, other: Self) -> Self { Self(self.0 & ! other.0) }
+
+	/// # Categories.
+	///
+	/// Return an iterator over all of the individual flags and their names,
+	/// in display order.
+	pub(super) fn categories() -> impl Iterator<Item=(Self, &'static str)> {
+		Self::NAMES.into_iter()
+	}
+
+	/// # Names.
+	///
+	/// Return an iterator over the names of the set flags.
+	pub(super) fn names(self) -> impl Iterator<Item=&'static str> {
+		Self::NAMES.into_iter()
+			.filter_map(move |(flag, name)|
+				if self.contains(flag) { Some(name) }
+				else { None }
+			)
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_from_list() {
+		assert_eq!(
+			ContentWarnings::from_list("script,external-ref, DATA-URI"),
+			Some(ContentWarnings::SCRIPTS | ContentWarnings::EXTERNAL_REFS | ContentWarnings::DATA_URIS),
+		);
+		assert_eq!(ContentWarnings::from_list("script,apples"), None);
+		assert_eq!(ContentWarnings::from_list(""), None);
+
+		// Make sure the names round-trip.
+		let all = ContentWarnings::NAMES.iter()
+			.fold(ContentWarnings::NONE, |acc, (flag, _)| acc | *flag);
+		assert_eq!(all, ContentWarnings::ALL, "ALL is out of sync with the names.");
+		assert_eq!(ContentWarnings::from_list(&all.to_string()), Some(all));
+	}
+
+	#[test]
+	fn test_without() {
+		let all = ContentWarnings::ALL.without(ContentWarnings::SCRIPTS);
+		assert!(! all.contains(ContentWarnings::SCRIPTS));
+		assert!(all.contains(ContentWarnings::EXTERNAL_REFS));
+		assert_eq!(
+			ContentWarnings::SCRIPTS.without(ContentWarnings::SCRIPTS),
+			ContentWarnings::NONE,
+		);
+		assert_eq!(
+			ContentWarnings::EXTERNAL.without(ContentWarnings::SCRIPTS),
+			ContentWarnings::EXTERNAL,
+		);
+	}
+}